@@ -1,9 +1,26 @@
 import { useGameStore, DiskColor } from "@/store/gameStore";
-import { makeMove, resign } from "@/services/ws";
+import { makeMove, requestHint, resign } from "@/services/ws";
+
+const formatTime = (ms: number, periods: number) => {
+  const seconds = Math.ceil(ms / 1000);
+  const time = `${Math.floor(seconds / 60)}:${String(seconds % 60).padStart(2, "0")}`;
+  return periods > 0 ? `${time} (${periods})` : time;
+};
 
 export default function GameBoard() {
-  const { board, currentPlayer, yourColor, validMoves, opponent } =
-    useGameStore();
+  const {
+    board,
+    currentPlayer,
+    yourColor,
+    validMoves,
+    holes,
+    clock,
+    passedColor,
+    lastMove,
+    blackCount,
+    whiteCount,
+    opponent,
+  } = useGameStore();
 
   const isYourTurn = currentPlayer === yourColor;
 
@@ -25,24 +42,52 @@ export default function GameBoard() {
         <div className="text-white">
           <p>You: {yourColor === "black" ? "Black" : "White"}</p>
           <p>Opponent: {opponent}</p>
+          <p>
+            Black {blackCount} - {whiteCount} White
+          </p>
+          {clock && (
+            <p>
+              {formatTime(clock.black_ms, clock.black_periods)} -{" "}
+              {formatTime(clock.white_ms, clock.white_periods)}
+            </p>
+          )}
           <p>{isYourTurn ? "Your turn" : "Opponent's turn"}</p>
+          {passedColor && (
+            <p className="text-yellow-400">
+              {passedColor === "black" ? "Black" : "White"} passes
+            </p>
+          )}
         </div>
 
-        <button
-          onClick={handleResign}
-          className="bg-red-600 hover:bg-red-700 text-white px-4 py-2 rounded"
-        >
-          Resign
-        </button>
+        <div className="flex items-start space-x-2">
+          <button
+            onClick={requestHint}
+            className="bg-blue-600 hover:bg-blue-700 text-white px-4 py-2 rounded"
+          >
+            Hint
+          </button>
+
+          <button
+            onClick={handleResign}
+            className="bg-red-600 hover:bg-red-700 text-white px-4 py-2 rounded"
+          >
+            Resign
+          </button>
+        </div>
       </div>
 
-      <div className="grid grid-cols-8 gap-1 bg-green-800 p-4 rounded-lg">
+      <div
+        className="grid gap-1 bg-green-800 p-4 rounded-lg"
+        style={{ gridTemplateColumns: `repeat(${board.length}, minmax(0, 1fr))` }}
+      >
         {board.map((row, y) =>
           row.map((cell, x) => (
             <div
               key={`${x}-${y}`}
               className={`w-14 h-14 flex items-center justify-center border border-green-900 ${
-                validMoves.some(([mx, my]) => mx === x && my === y)
+                holes.some(([hx, hy]) => hx === x && hy === y)
+                  ? "bg-gray-900"
+                  : validMoves.some(([mx, my]) => mx === x && my === y)
                   ? "bg-green-700 cursor-pointer"
                   : "bg-green-800"
               }`}
@@ -52,6 +97,10 @@ export default function GameBoard() {
                 <div
                   className={`w-10 h-10 rounded-full ${
                     cell === "black" ? "bg-black" : "bg-white"
+                  } ${
+                    lastMove && lastMove[0] === x && lastMove[1] === y
+                      ? "ring-4 ring-red-500"
+                      : ""
                   }`}
                 ></div>
               )}
@@ -1,7 +1,7 @@
 import { useGameStore } from "@/store/gameStore";
 
 export default function GameOverScreen() {
-  const { winner, gameOverReason, resetGame } = useGameStore();
+  const { winner, gameOverReason, finalScore, resetGame } = useGameStore();
 
   return (
     <div className="flex flex-col items-center justify-center space-y-6 p-8 bg-gray-800 rounded-lg shadow-lg">
@@ -15,6 +15,12 @@ export default function GameOverScreen() {
         <p className="text-2xl text-gray-400">Game ended in a draw</p>
       )}
 
+      {finalScore && (
+        <p className="text-xl text-white">
+          Black {finalScore.black} - {finalScore.white} White
+        </p>
+      )}
+
       <button
         onClick={resetGame}
         className="bg-blue-600 hover:bg-blue-700 text-white font-bold py-2 px-6 rounded mt-4"
@@ -1,20 +1,190 @@
-import { joinQueue } from "@/services/ws";
+import { joinQueue, playComputer } from "@/services/ws";
 import { useGameStore } from "@/store/gameStore";
+import { useState } from "react";
+
+const BOARD_SIZES = [6, 8, 10, 12];
+const VARIANTS = [
+  { value: "standard", label: "Standard" },
+  { value: "anti_reversi", label: "Anti-reversi (fewest discs wins)" },
+];
+const START_POSITIONS = [
+  { value: "standard", label: "Standard start" },
+  { value: "xot", label: "XOT opening (8x8 only)" },
+  { value: "random", label: "Random 8-move start" },
+];
+const MASKS = [
+  { value: "full", label: "No blocked squares" },
+  { value: "no_corners", label: "Corners removed" },
+  { value: "cross", label: "Cross-shaped board" },
+  { value: "random", label: "Random blocked squares" },
+];
+const TIME_CONTROLS = [
+  { label: "No clock", value: { type: "unlimited" } },
+  { label: "5 min sudden death", value: { type: "sudden_death", base_secs: 300 } },
+  {
+    label: "3 min + 2 sec (Fischer)",
+    value: { type: "fischer", base_secs: 180, increment_secs: 2 },
+  },
+  {
+    label: "5 min + 3 x 30 sec byo-yomi",
+    value: { type: "byo_yomi", base_secs: 300, period_secs: 30, periods: 3 },
+  },
+];
+
+const DIFFICULTIES = [
+  { value: "random", label: "Computer: Random" },
+  { value: "easy", label: "Computer: Easy" },
+  { value: "medium", label: "Computer: Medium" },
+  { value: "hard", label: "Computer: Hard" },
+  { value: "expert", label: "Computer: Expert" },
+];
+
+const ENGINES = [
+  { value: "minimax", label: "Alpha-beta search" },
+  { value: "mcts", label: "Monte Carlo tree search" },
+];
 
 const LobbyScreen = () => {
   const username = useGameStore((state) => state.username);
+  const [boardSize, setBoardSize] = useState(8);
+  const [variant, setVariant] = useState("standard");
+  const [start, setStart] = useState("standard");
+  const [mask, setMask] = useState("full");
+  const [timeControl, setTimeControl] = useState(0);
+  const [casual, setCasual] = useState(false);
+  const [difficulty, setDifficulty] = useState("medium");
+  const [engine, setEngine] = useState("minimax");
 
   return (
     <div className="flex flex-col items-center justify-center space-y-6 p-8 bg-gray-800 rounded-lg shadow-log">
       <h1 className="text-3xl font-bold text-white">Lobby</h1>
       <p className="text-white">Welcome, {username}</p>
 
+      <select
+        value={boardSize}
+        onChange={(e) => setBoardSize(Number(e.target.value))}
+        className="bg-gray-700 text-white rounded px-3 py-2"
+      >
+        {BOARD_SIZES.map((size) => (
+          <option key={size} value={size}>
+            {size}x{size}
+          </option>
+        ))}
+      </select>
+
+      <select
+        value={variant}
+        onChange={(e) => setVariant(e.target.value)}
+        className="bg-gray-700 text-white rounded px-3 py-2"
+      >
+        {VARIANTS.map(({ value, label }) => (
+          <option key={value} value={value}>
+            {label}
+          </option>
+        ))}
+      </select>
+
+      <select
+        value={start}
+        onChange={(e) => setStart(e.target.value)}
+        className="bg-gray-700 text-white rounded px-3 py-2"
+      >
+        {START_POSITIONS.map(({ value, label }) => (
+          <option key={value} value={value}>
+            {label}
+          </option>
+        ))}
+      </select>
+
+      <select
+        value={mask}
+        onChange={(e) => setMask(e.target.value)}
+        className="bg-gray-700 text-white rounded px-3 py-2"
+      >
+        {MASKS.map(({ value, label }) => (
+          <option key={value} value={value}>
+            {label}
+          </option>
+        ))}
+      </select>
+
+      <select
+        value={timeControl}
+        onChange={(e) => setTimeControl(Number(e.target.value))}
+        className="bg-gray-700 text-white rounded px-3 py-2"
+      >
+        {TIME_CONTROLS.map(({ label }, index) => (
+          <option key={label} value={index}>
+            {label}
+          </option>
+        ))}
+      </select>
+
+      <label className="flex items-center space-x-2 text-white">
+        <input
+          type="checkbox"
+          checked={casual}
+          onChange={(e) => setCasual(e.target.checked)}
+        />
+        <span>Casual (unrated, hints allowed)</span>
+      </label>
+
       <button
-        onClick={joinQueue}
+        onClick={() =>
+          joinQueue(
+            boardSize,
+            variant,
+            start,
+            mask,
+            TIME_CONTROLS[timeControl].value,
+            casual
+          )
+        }
         className="bg-green-600 hover:bg-green-700 text-white font-bold py-3 px-6 rounded-lg text-xl"
       >
         Find Match
       </button>
+
+      <select
+        value={difficulty}
+        onChange={(e) => setDifficulty(e.target.value)}
+        className="bg-gray-700 text-white rounded px-3 py-2"
+      >
+        {DIFFICULTIES.map(({ value, label }) => (
+          <option key={value} value={value}>
+            {label}
+          </option>
+        ))}
+      </select>
+
+      <select
+        value={engine}
+        onChange={(e) => setEngine(e.target.value)}
+        className="bg-gray-700 text-white rounded px-3 py-2"
+      >
+        {ENGINES.map(({ value, label }) => (
+          <option key={value} value={value}>
+            {label}
+          </option>
+        ))}
+      </select>
+
+      <button
+        onClick={() =>
+          playComputer(
+            engine,
+            difficulty,
+            boardSize,
+            variant,
+            start,
+            mask,
+            TIME_CONTROLS[timeControl].value
+          )
+        }
+        className="bg-blue-600 hover:bg-blue-700 text-white font-bold py-3 px-6 rounded-lg text-xl"
+      >
+        Play vs Computer
+      </button>
     </div>
   );
 };
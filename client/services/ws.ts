@@ -1,5 +1,4 @@
 import { useGameStore } from "@/store/gameStore";
-import { calculateValidMoves } from "@/utils/gameLogic";
 
 let socket: WebSocket | null = null;
 let heartbeatInterval: NodeJS.Timeout | null = null;
@@ -90,9 +89,49 @@ export const authenticate = (username: string): void => {
   useGameStore.getState().setUsername(username);
 };
 
-export const joinQueue = (): void => {
+export const joinQueue = (
+  boardSize: number = 8,
+  variant: string = "standard",
+  start: string = "standard",
+  mask: string = "full",
+  timeControl: object = { type: "unlimited" },
+  casual: boolean = false
+): void => {
   sendMessage({
     type: "join_queue",
+    payload: {
+      board_size: boardSize,
+      variant,
+      start,
+      mask,
+      time_control: timeControl,
+      casual,
+    },
+  });
+
+  useGameStore.getState().startMatchSearch();
+};
+
+export const playComputer = (
+  engine: string = "minimax",
+  difficulty: string = "medium",
+  boardSize: number = 8,
+  variant: string = "standard",
+  start: string = "standard",
+  mask: string = "full",
+  timeControl: object = { type: "unlimited" }
+): void => {
+  sendMessage({
+    type: "play_computer",
+    payload: {
+      engine,
+      difficulty,
+      board_size: boardSize,
+      variant,
+      start,
+      mask,
+      time_control: timeControl,
+    },
   });
 
   useGameStore.getState().startMatchSearch();
@@ -119,6 +158,34 @@ export const resign = (): void => {
   });
 };
 
+export const requestHint = (): void => {
+  // カジュアル対局とコンピューター対局でのみ使える（回数制限あり）
+  sendMessage({
+    type: "request_hint",
+    payload: {},
+  });
+};
+
+export const requestAnalysis = (gameId: string): void => {
+  // 解析が終わるとanalysis_reportが届く（終局後は自動で届く）
+  sendMessage({
+    type: "request_analysis",
+    payload: { game_id: gameId },
+  });
+};
+
+export const exploreOpening = (
+  board?: string,
+  side?: string,
+  variant?: string
+): void => {
+  // 盤面を省略すると対局中の現在の局面の定石を返す
+  sendMessage({
+    type: "explore_opening",
+    payload: { board, side, variant },
+  });
+};
+
 export const disconnect = (): void => {
   if (socket) {
     socket.close();
@@ -146,11 +213,17 @@ const handleServerMessage = (message: any): void => {
       break;
 
     case "error":
-      console.error("Error:", message.message);
+      // ゲームのエラーには機械可読なコード（例: "not_your_turn"）が付く
+      console.error("Error:", message.code ?? "", message.message);
       break;
 
     case "match_found":
-      console.log("Match found with:", message.opponent);
+      console.log("Match found with:", message.opponent, message.variant);
+      if (message.handicap) {
+        console.log(
+          `Handicap: ${message.handicap.discs} corner disc(s) for ${message.handicap.color}`
+        );
+      }
       gameStore.setMatchFound(message.opponent);
       break;
 
@@ -162,20 +235,75 @@ const handleServerMessage = (message: any): void => {
 
       gameStore.updateBoard(board, currentPlayer, yourColor);
 
-      // 有効な手を計算
-      const validMoves = calculateValidMoves(board, currentPlayer);
-      gameStore.setValidMoves(validMoves);
+      // サーバーが判定した有効な手
+      gameStore.setValidMoves(message.legal_moves);
+
+      // 石を置けない穴のマス
+      gameStore.setHoles(message.holes);
+
+      // 両プレイヤーの残り時間
+      gameStore.setClock(message.clock);
+
+      // 直前の手と反転した石
+      gameStore.setMoveInfo(
+        message.last_move,
+        message.flipped,
+        message.black_count,
+        message.white_count
+      );
+      break;
+
+    case "pass":
+      console.log(`${message.player} (${message.color}) passes`);
+      gameStore.setPassed(message.color);
       break;
 
     case "game_over":
-      console.log("Game over. Reason:", message.reason);
+      console.log("Game over. Reason:", message.reason, `(${message.result.termination})`);
+      if (message.opening) {
+        console.log("Opening:", message.opening);
+      }
       if (message.winner) {
         console.log("Winner:", message.winner);
       } else {
         console.log("Game ended in a draw");
       }
 
-      gameStore.setGameOver(message.winner, message.reason);
+      gameStore.setGameOver(message.winner, message.reason, message.result.score);
+      break;
+
+    case "hint":
+      console.log(
+        `Hint (${message.exact ? "exact" : "estimated"}):`,
+        message.moves
+          .map((move: any) => `${move.notation} ${move.score}`)
+          .join(", ")
+      );
+      if (message.hints_remaining !== null) {
+        console.log(`${message.hints_remaining} hint(s) left`);
+      }
+      break;
+
+    case "analysis_report": {
+      const report = message.report;
+      console.log(`Analysis of ${report.black} vs ${report.white}:`, report.graph);
+      for (const move of report.key_mistakes) {
+        console.log(
+          `${move.ply}. ${move.notation} (${move.color}) ${move.annotation}: ` +
+            `best was ${move.best_move} (loss ${move.loss}${move.exact ? " discs" : ""})`
+        );
+      }
+      break;
+    }
+
+    case "opening_explorer":
+      console.log(`Opening book: ${message.games} game(s) from this position`);
+      for (const move of message.moves) {
+        console.log(
+          `${move.notation}: ${move.games} games (${move.imported_games} imported), +${move.wins} =${move.draws} -${move.losses}, ` +
+            `avg disc diff ${move.average_disc_differential.toFixed(1)}`
+        );
+      }
       break;
 
     default:
@@ -2,17 +2,33 @@ import { create } from "zustand";
 
 export type DiskColor = "black" | "white";
 
+export type ClockState = {
+  black_ms: number;
+  white_ms: number;
+  black_periods: number;
+  white_periods: number;
+  running: DiskColor | null;
+};
+
 export type GameState = {
   board: Array<Array<DiskColor | null>>;
   currentPlayer: DiskColor;
   yourColor: DiskColor | null;
   validMoves: Array<[number, number]>;
+  holes: Array<[number, number]>;
+  clock: ClockState | null;
+  passedColor: DiskColor | null;
+  lastMove: [number, number] | null;
+  flipped: Array<[number, number]>;
+  blackCount: number;
+  whiteCount: number;
   opponent: string | null;
   isInGame: boolean;
   isSearchingMatch: boolean;
   isGameOver: boolean;
   winner: string | null;
   gameOverReason: string | null;
+  finalScore: { black: number; white: number } | null;
   username: string | null;
   isConnected: boolean;
 };
@@ -29,9 +45,22 @@ export type GameActions = {
     yourColor: DiskColor
   ) => void;
   setValidMoves: (moves: Array<[number, number]>) => void;
+  setHoles: (holes: Array<[number, number]>) => void;
+  setClock: (clock: ClockState | null) => void;
+  setPassed: (color: DiskColor) => void;
+  setMoveInfo: (
+    lastMove: [number, number] | null,
+    flipped: Array<[number, number]>,
+    blackCount: number,
+    whiteCount: number
+  ) => void;
   makeMove: (x: number, y: number) => void;
   resign: () => void;
-  setGameOver: (winner: string | null, reason: string) => void;
+  setGameOver: (
+    winner: string | null,
+    reason: string,
+    finalScore: { black: number; white: number } | null
+  ) => void;
   resetGame: () => void;
 };
 
@@ -42,12 +71,20 @@ const initialState: GameState = {
   currentPlayer: "black",
   yourColor: null,
   validMoves: [],
+  holes: [],
+  clock: null,
+  passedColor: null,
+  lastMove: null,
+  flipped: [],
+  blackCount: 2,
+  whiteCount: 2,
   opponent: null,
   isInGame: false,
   isSearchingMatch: false,
   isGameOver: false,
   winner: null,
   gameOverReason: null,
+  finalScore: null,
   username: null,
   isConnected: false,
 };
@@ -75,19 +112,30 @@ export const useGameStore = create<GameState & GameActions>((set, get) => ({
       board,
       currentPlayer,
       yourColor,
+      passedColor: null,
     }),
 
   setValidMoves: (moves) => set({ validMoves: moves }),
 
+  setHoles: (holes) => set({ holes }),
+
+  setClock: (clock) => set({ clock }),
+
+  setPassed: (color) => set({ passedColor: color }),
+
+  setMoveInfo: (lastMove, flipped, blackCount, whiteCount) =>
+    set({ lastMove, flipped, blackCount, whiteCount }),
+
   makeMove: (x, y) => {},
   resign: () => {},
 
-  setGameOver: (winner, reason) =>
+  setGameOver: (winner, reason, finalScore) =>
     set({
       isGameOver: true,
       isInGame: false,
       winner,
       gameOverReason: reason,
+      finalScore,
     }),
 
   resetGame: () =>
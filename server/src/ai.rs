@@ -0,0 +1,630 @@
+//! # コンピューター対戦
+//!
+//! `game.rs`のルールに従って手を選ぶコンピューターのプレイヤー
+//!
+//! - 評価関数: マスごとの重み（隅を高く、隅の隣を低く）と着手可能数の差
+//! - 探索: 反復深化のαβ法。持ち時間を使い切った場合は最後に読み終えた深さの結果を使う
+//! - 終盤: 空きマスが難易度ごとの数以下になると`solver`で読み切る。
+//!   思考時間の半分で読み切れなかった場合は残りの時間でαβ法の結果を使う
+//! - `Engine::Mcts`を選ぶと評価関数の代わりに`mcts`のプレイアウトで手を選ぶ
+//!
+//! ## 関数
+//!
+//! - `choose_move`:           難易度に応じて手を選ぶ
+//! - `rank_moves`:            合法手を評価値の高い順に並べる（ヒントと局面の検討用）
+//! - `Computer::choose_move`: 対局中のゲームで手を選ぶ（MCTSの探索木を手の間で引き継ぐ）
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::bitboard::{Bitboard, Bits, Board, Geometry};
+use crate::game::{DiskColor, Game, Variant};
+use crate::mcts::{Mcts, MctsConfig, Playout};
+use crate::notation;
+use crate::solver;
+
+/// 着手可能数の差1つあたりの評価値
+const MOBILITY_WEIGHT: i32 = 8;
+/// 勝ちが確定した局面の評価値（これに石差を加える）
+pub const WIN_SCORE: i32 = 10_000;
+/// 評価値の範囲外の値
+const INFINITY: i32 = 1_000_000;
+/// 持ち時間を確認する間隔（局面数）
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+/// 合法手の評価で読み切りを行う空きマスの数
+const RANK_SOLVE_EMPTIES: usize = 14;
+/// 合法手の評価で読む最大の深さ
+const RANK_MAX_DEPTH: u32 = 32;
+/// 思考時間のうち読み切りに使う割合の逆数（残りは読み切れなかった場合の探索に使う）
+const SOLVE_BUDGET_DIVISOR: u32 = 2;
+
+/// コンピューターの強さ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    /// 合法手から無作為に選ぶ
+    Random,
+    /// 2手先まで読み、評価値にばらつきを加える
+    Easy,
+    /// 4手先まで読む
+    #[default]
+    Medium,
+    /// 1秒まで読み、終盤12マスを読み切る
+    Hard,
+    /// 3秒まで読み、終盤16マスを読み切る
+    Expert,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difficulty::Random => write!(f, "random"),
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+            Difficulty::Expert => write!(f, "expert"),
+        }
+    }
+}
+
+/// 手を選ぶ方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Engine {
+    /// 評価関数を使ったαβ法
+    #[default]
+    Minimax,
+    /// モンテカルロ木探索
+    Mcts,
+}
+
+impl fmt::Display for Engine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Engine::Minimax => write!(f, "minimax"),
+            Engine::Mcts => write!(f, "mcts"),
+        }
+    }
+}
+
+/// 難易度ごとの探索の設定
+struct Level {
+    /// 最大の読みの深さ
+    max_depth: u32,
+    /// 1手に使う時間
+    budget: Duration,
+    /// 読み切りを行う空きマスの数
+    solve_empties: usize,
+    /// 評価値に加えるばらつきの幅
+    noise: i32,
+    /// MCTSで1手あたりに行うプレイアウト回数
+    playouts: u32,
+}
+
+impl Difficulty {
+    /// 定石にある局面で定石手を打つか
+    pub fn uses_book(&self) -> bool {
+        *self != Difficulty::Random
+    }
+
+    /// 探索の設定（無作為に選ぶ場合はNone）
+    fn level(&self) -> Option<Level> {
+        match self {
+            Difficulty::Random => None,
+            Difficulty::Easy => Some(Level {
+                max_depth: 2,
+                budget: Duration::from_millis(200),
+                solve_empties: 0,
+                noise: 40,
+                playouts: 500,
+            }),
+            Difficulty::Medium => Some(Level {
+                max_depth: 4,
+                budget: Duration::from_millis(500),
+                solve_empties: 8,
+                noise: 0,
+                playouts: 5_000,
+            }),
+            Difficulty::Hard => Some(Level {
+                max_depth: 10,
+                budget: Duration::from_secs(1),
+                solve_empties: 12,
+                noise: 0,
+                playouts: 50_000,
+            }),
+            Difficulty::Expert => Some(Level {
+                max_depth: 32,
+                budget: Duration::from_secs(3),
+                solve_empties: 16,
+                noise: 0,
+                playouts: 300_000,
+            }),
+        }
+    }
+}
+
+/// マスの重み（隅を高く、隅に隣接するマスを低くする）
+fn square_weight(x: usize, y: usize, size: usize) -> i32 {
+    let last = size - 1;
+    let on_edge = |v: usize| v == 0 || v == last;
+    let next_to_edge = |v: usize| v == 1 || v == last - 1;
+
+    if on_edge(x) && on_edge(y) {
+        100
+    } else if next_to_edge(x) && next_to_edge(y) {
+        // 隅の斜め隣（Xマス）
+        -50
+    } else if (on_edge(x) && next_to_edge(y)) || (on_edge(y) && next_to_edge(x)) {
+        // 辺上の隅の隣（Cマス）
+        -20
+    } else if on_edge(x) || on_edge(y) {
+        10
+    } else {
+        0
+    }
+}
+
+/// 1回の思考の探索状態
+struct Search<B: Bits> {
+    geometry: &'static Geometry<B>,
+    holes: B,
+    variant: Variant,
+    /// ビット位置ごとのマスの重み
+    weights: Vec<i32>,
+    deadline: Instant,
+    nodes: u64,
+    /// 持ち時間を使い切って探索を打ち切ったか
+    aborted: bool,
+}
+
+impl<B: Bits> Search<B> {
+    fn new(board: &Bitboard<B>, variant: Variant, deadline: Instant) -> Self {
+        let size = board.size();
+        Self {
+            geometry: board.geometry(),
+            holes: board.holes(),
+            variant,
+            weights: (0..size * size)
+                .map(|index| square_weight(index % size, index / size, size))
+                .collect(),
+            deadline,
+            nodes: 0,
+            aborted: false,
+        }
+    }
+
+    #[inline]
+    fn legal_moves(&self, player: B, opponent: B) -> B {
+        self.geometry.legal_moves(player, opponent, self.holes)
+    }
+
+    #[inline]
+    fn play(&self, player: B, opponent: B, index: usize) -> (B, B) {
+        let bit = B::bit(index);
+        let flipped = self.geometry.flips(player, opponent, bit);
+        (player | bit | flipped, opponent & !flipped)
+    }
+
+    fn positional(&self, discs: B) -> i32 {
+        discs.indices().map(|index| self.weights[index]).sum()
+    }
+
+    /// 手番側から見た局面の評価値
+    ///
+    /// アンチリバーシでは石を置いたマスの重みを逆に数える
+    fn evaluate(&self, player: B, opponent: B) -> i32 {
+        let positional = self.positional(player) - self.positional(opponent);
+        let positional = match self.variant {
+            Variant::Standard => positional,
+            Variant::AntiReversi => -positional,
+        };
+        let mobility = self.legal_moves(player, opponent).count() as i32
+            - self.legal_moves(opponent, player).count() as i32;
+
+        positional + MOBILITY_WEIGHT * mobility
+    }
+
+    /// 終局した局面の手番側から見た評価値（勝敗を石差より優先する）
+    fn final_score(&self, player: B, opponent: B) -> i32 {
+        let margin = match self.variant {
+            Variant::Standard => player.count() as i32 - opponent.count() as i32,
+            Variant::AntiReversi => opponent.count() as i32 - player.count() as i32,
+        };
+        margin.signum() * WIN_SCORE + margin
+    }
+
+    /// 重みの大きいマスから順に並べた合法手
+    fn ordered(&self, moves: B) -> Vec<usize> {
+        let mut ordered: Vec<usize> = moves.indices().collect();
+        ordered.sort_by_key(|&index| std::cmp::Reverse(self.weights[index]));
+        ordered
+    }
+
+    /// 深さ`depth`まで読んだ手番側から見た評価値
+    fn negamax(
+        &mut self,
+        player: B,
+        opponent: B,
+        depth: u32,
+        mut alpha: i32,
+        beta: i32,
+        passed: bool,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(DEADLINE_CHECK_INTERVAL) && Instant::now() >= self.deadline {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0;
+        }
+
+        let moves = self.legal_moves(player, opponent);
+        if moves.is_zero() {
+            // 両者とも打てなければ終局、そうでなければパス（深さは消費しない）
+            if passed {
+                return self.final_score(player, opponent);
+            }
+            return -self.negamax(opponent, player, depth, -beta, -alpha, true);
+        }
+        if depth == 0 {
+            return self.evaluate(player, opponent);
+        }
+
+        let mut best = -INFINITY;
+        for index in self.ordered(moves) {
+            let (next_player, next_opponent) = self.play(player, opponent, index);
+            let value = -self.negamax(next_opponent, next_player, depth - 1, -beta, -alpha, false);
+            best = best.max(value);
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+
+    /// 深さ`depth`まで読んだ各合法手の評価値（時間切れの場合はNone）
+    ///
+    /// `exact`でない場合、最善手以外の評価値はそれ以下であることだけが保証される
+    fn root(
+        &mut self,
+        player: B,
+        opponent: B,
+        moves: &[usize],
+        depth: u32,
+        exact: bool,
+    ) -> Option<Vec<(usize, i32)>> {
+        let mut alpha = -INFINITY;
+        let mut values = Vec::with_capacity(moves.len());
+
+        for &index in moves {
+            let (next_player, next_opponent) = self.play(player, opponent, index);
+            let beta = if exact { INFINITY } else { -alpha };
+            let value = -self.negamax(
+                next_opponent,
+                next_player,
+                depth - 1,
+                -INFINITY,
+                beta,
+                false,
+            );
+            if self.aborted {
+                return None;
+            }
+            alpha = alpha.max(value);
+            values.push((index, value));
+        }
+
+        Some(values)
+    }
+}
+
+/// 反復深化で手を選ぶ
+fn search_bitboard<B: Bits, R: Rng>(
+    board: &Bitboard<B>,
+    color: DiskColor,
+    variant: Variant,
+    level: &Level,
+    budget: Duration,
+    rng: &mut R,
+) -> Option<(usize, usize)> {
+    let mut search = Search::new(board, variant, Instant::now() + budget);
+    let (player, opponent) = (board.discs(color), board.discs(color.opposite()));
+    let mut moves = search.ordered(search.legal_moves(player, opponent));
+    if moves.is_empty() {
+        return None;
+    }
+
+    let mut values: Vec<(usize, i32)> = moves.iter().map(|&index| (index, 0)).collect();
+    for depth in 1..=level.max_depth {
+        let Some(mut completed) = search.root(player, opponent, &moves, depth, level.noise > 0)
+        else {
+            break;
+        };
+
+        // 次の深さは評価値の高い手から読む
+        completed.sort_by_key(|&(_, value)| std::cmp::Reverse(value));
+        moves = completed.iter().map(|&(index, _)| index).collect();
+        values = completed;
+
+        // 勝敗が読み切れた場合はそれ以上読まない
+        if values[0].1.abs() >= WIN_SCORE {
+            break;
+        }
+    }
+
+    let (index, _) = if level.noise > 0 {
+        values
+            .iter()
+            .map(|&(index, value)| (index, value + rng.gen_range(-level.noise..=level.noise)))
+            .max_by_key(|&(_, value)| value)?
+    } else {
+        values[0]
+    };
+    Some(board.coordinates(index))
+}
+
+/// 評価した合法手
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankedMove {
+    /// 手の座標（`[x, y]`）
+    pub square: [usize; 2],
+    /// 標準座標表記（例: `f5`）
+    pub notation: String,
+    /// 手番側から見た評価値（読み切った場合は最終的な石差）
+    pub score: i32,
+}
+
+/// 反復深化で各合法手の正確な評価値を求める
+fn rank_bitboard<B: Bits>(
+    board: &Bitboard<B>,
+    color: DiskColor,
+    variant: Variant,
+    budget: Duration,
+) -> Vec<(usize, i32)> {
+    let mut search = Search::new(board, variant, Instant::now() + budget);
+    let (player, opponent) = (board.discs(color), board.discs(color.opposite()));
+    let mut moves = search.ordered(search.legal_moves(player, opponent));
+
+    let mut values: Vec<(usize, i32)> = moves.iter().map(|&index| (index, 0)).collect();
+    for depth in 1..=RANK_MAX_DEPTH {
+        let Some(mut completed) = search.root(player, opponent, &moves, depth, true) else {
+            break;
+        };
+        completed.sort_by_key(|&(_, value)| std::cmp::Reverse(value));
+        moves = completed.iter().map(|&(index, _)| index).collect();
+        values = completed;
+
+        // すべての手の勝敗が読み切れた場合はそれ以上読まない
+        if values.iter().all(|&(_, value)| value.abs() >= WIN_SCORE) {
+            break;
+        }
+    }
+    values
+}
+
+/// `color`の手番の局面の合法手を評価値の高い順に並べる
+///
+/// 空きマスが少ない局面は`budget`の半分の時間で読み切って石差を返し（2番目の値がtrue）、
+/// それ以外や読み切れなかった場合は`budget`の残りの時間まで読んだ評価値を返す
+pub fn rank_moves(
+    board: &Board,
+    color: DiskColor,
+    variant: Variant,
+    budget: Duration,
+) -> (Vec<RankedMove>, bool) {
+    let ranked = |square: (usize, usize), score: i32| RankedMove {
+        square: [square.0, square.1],
+        notation: notation::format_square(square.0, square.1),
+        score,
+    };
+
+    let started = Instant::now();
+    if board.empty_count() <= RANK_SOLVE_EMPTIES
+        && let Ok(solved) =
+            solver::solve_moves(board, color, variant, Some(budget / SOLVE_BUDGET_DIVISOR))
+    {
+        let moves = solved
+            .into_iter()
+            .map(|mv| ranked(mv.square, mv.margin))
+            .collect();
+        return (moves, true);
+    }
+
+    let budget = budget.saturating_sub(started.elapsed());
+
+    let moves = match board {
+        Board::Narrow(board) => rank_bitboard(board, color, variant, budget)
+            .into_iter()
+            .map(|(index, score)| ranked(board.coordinates(index), score))
+            .collect(),
+        Board::Wide(board) => rank_bitboard(board, color, variant, budget)
+            .into_iter()
+            .map(|(index, score)| ranked(board.coordinates(index), score))
+            .collect(),
+    };
+    (moves, false)
+}
+
+/// `color`の手番の局面で難易度に応じて手を選ぶ（打てる手がない場合はNone）
+///
+/// `time_limit`を指定すると難易度ごとの思考時間より短い場合にそちらを使う
+pub fn choose_move<R: Rng>(
+    board: &Board,
+    color: DiskColor,
+    variant: Variant,
+    difficulty: Difficulty,
+    time_limit: Option<Duration>,
+    rng: &mut R,
+) -> Option<(usize, usize)> {
+    let moves = board.legal_moves(color);
+    let Some(level) = difficulty.level() else {
+        return moves.choose(rng).copied();
+    };
+    if moves.len() <= 1 {
+        return moves.first().copied();
+    }
+
+    let budget = time_limit.map_or(level.budget, |limit| limit.min(level.budget));
+    let started = Instant::now();
+    if let Some(square) = solve_endgame(board, color, variant, &level, budget) {
+        return Some(square);
+    }
+
+    let budget = budget.saturating_sub(started.elapsed());
+    match board {
+        Board::Narrow(board) => search_bitboard(board, color, variant, &level, budget, rng),
+        Board::Wide(board) => search_bitboard(board, color, variant, &level, budget, rng),
+    }
+}
+
+/// 空きマスが難易度ごとの数以下なら`budget`の一部の時間で読み切って最善手を返す
+///
+/// 読み切れなかった場合や打てる手がない場合はNone
+fn solve_endgame(
+    board: &Board,
+    color: DiskColor,
+    variant: Variant,
+    level: &Level,
+    budget: Duration,
+) -> Option<(usize, usize)> {
+    if board.empty_count() > level.solve_empties {
+        return None;
+    }
+    solver::solve(board, color, variant, Some(budget / SOLVE_BUDGET_DIVISOR))
+        .ok()?
+        .best_move
+}
+
+/// 対局中のコンピューターのプレイヤー
+///
+/// MCTSの場合は探索木を保持し、同じゲームで続けて手を選ぶときに引き継ぐ
+pub struct Computer {
+    difficulty: Difficulty,
+    /// MCTSのプレイヤー（評価関数で選ぶ場合や無作為に選ぶ場合はNone）
+    mcts: Option<Mcts>,
+}
+
+impl Computer {
+    pub fn new(engine: Engine, difficulty: Difficulty, variant: Variant) -> Self {
+        let mcts = match (engine, difficulty.level()) {
+            (Engine::Mcts, Some(level)) => Some(Mcts::new(MctsConfig {
+                playouts: level.playouts,
+                // 隅を優先する方針はアンチリバーシには当てはまらない
+                playout: match variant {
+                    Variant::Standard => Playout::Light,
+                    Variant::AntiReversi => Playout::Random,
+                },
+                ..MctsConfig::default()
+            })),
+            _ => None,
+        };
+        Self { difficulty, mcts }
+    }
+
+    /// `game`の手番側の手を選ぶ（打てる手がない場合はNone）
+    ///
+    /// `time_limit`の扱いは`choose_move`と同じ
+    pub fn choose_move<R: Rng>(
+        &mut self,
+        game: &Game,
+        time_limit: Option<Duration>,
+        rng: &mut R,
+    ) -> Option<(usize, usize)> {
+        let board = game.board();
+        let color = game.current_color();
+        let variant = game.config().variant;
+        let (Some(mcts), Some(level)) = (&mut self.mcts, self.difficulty.level()) else {
+            return choose_move(board, color, variant, self.difficulty, time_limit, rng);
+        };
+
+        // 終盤は評価関数の場合と同じく読み切る
+        let budget = time_limit.map_or(level.budget, |limit| limit.min(level.budget));
+        let started = Instant::now();
+        if let Some(square) = solve_endgame(board, color, variant, &level, budget) {
+            return Some(square);
+        }
+
+        let budget = budget.saturating_sub(started.elapsed());
+        mcts.choose_move(game, Some(budget), rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameConfig;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    /// 初期配置から無作為に打ち進めて空きマスが`empties`になったゲーム
+    fn random_game(empties: usize, rng: &mut StdRng) -> Game {
+        loop {
+            let mut game = Game::new(
+                "ai".to_string(),
+                "black-id".to_string(),
+                "black".to_string(),
+                "white-id".to_string(),
+                "white".to_string(),
+                GameConfig::default(),
+                None,
+            );
+            while game.board().empty_count() > empties {
+                let Some(&(x, y)) = game.legal_moves().choose(rng) else {
+                    break;
+                };
+                let player_id = game.player_id(game.current_color()).to_string();
+                game.make_move(&player_id, x, y).unwrap();
+            }
+            if !game.is_over() && game.legal_moves().len() > 1 {
+                return game;
+            }
+        }
+    }
+
+    #[test]
+    fn endgame_solving_respects_time_limit() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let game = random_game(16, &mut rng);
+        let limit = Some(Duration::from_millis(5));
+
+        for engine in [Engine::Minimax, Engine::Mcts] {
+            let mut computer = Computer::new(engine, Difficulty::Expert, Variant::Standard);
+            let started = Instant::now();
+            let square = computer.choose_move(&game, limit, &mut rng).unwrap();
+            assert!(started.elapsed() < Duration::from_secs(1), "{}", engine);
+            assert!(game.legal_moves().contains(&square));
+        }
+    }
+
+    #[test]
+    fn chooses_a_move_without_time_left() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let game = random_game(40, &mut rng);
+
+        for engine in [Engine::Minimax, Engine::Mcts] {
+            let mut computer = Computer::new(engine, Difficulty::Hard, Variant::Standard);
+            let square = computer
+                .choose_move(&game, Some(Duration::ZERO), &mut rng)
+                .unwrap();
+            assert!(game.legal_moves().contains(&square));
+        }
+    }
+
+    #[test]
+    fn rank_moves_is_exact_in_short_endgames() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let game = random_game(10, &mut rng);
+        let board = game.board();
+        let color = game.current_color();
+
+        let (moves, exact) = rank_moves(board, color, Variant::Standard, Duration::from_secs(5));
+        assert!(exact);
+        assert_eq!(moves.len(), game.legal_moves().len());
+        let solution = solver::solve(board, color, Variant::Standard, None).unwrap();
+        assert_eq!(moves[0].score, solution.margin);
+    }
+}
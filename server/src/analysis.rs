@@ -0,0 +1,265 @@
+//! # 対局の解析
+//!
+//! 終了した対局の手を`move_history`から再生し、各局面を`ai::rank_moves`で評価して
+//! 実際に打った手と最善手の評価値の差から悪手を見つける
+//!
+//! - 空きマスが少ない局面は読み切った石差、それ以外は途中までの評価値で比べる
+//! - 評価値の差が大きい手を疑問手（`Mistake`）、さらに大きい手や勝ちを逃した手を悪手（`Blunder`）とする
+//! - パスは評価しない
+//!
+//! ## 関数
+//!
+//! - `analyze`: 対局を解析して手ごとの注釈と評価値の推移を作成
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::ai::{self, WIN_SCORE};
+use crate::game::{DiskColor, Game, MoveAction};
+use crate::notation;
+
+/// 読み切った局面で疑問手とする石差の損失
+const MISTAKE_DISCS: i32 = 4;
+/// 読み切った局面で悪手とする石差の損失
+const BLUNDER_DISCS: i32 = 10;
+/// 読み切れない局面で疑問手とする評価値の損失
+const MISTAKE_EVALUATION: i32 = 40;
+/// 読み切れない局面で悪手とする評価値の損失
+const BLUNDER_EVALUATION: i32 = 100;
+/// 報告に含める主な悪手の数
+const KEY_MISTAKES: usize = 5;
+
+/// 手の評価（悪い評価ほど大きい）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Annotation {
+    /// 最善手
+    Best,
+    /// 最善手ではないが損失が小さい手
+    Good,
+    /// 疑問手
+    Mistake,
+    /// 悪手
+    Blunder,
+}
+
+/// 注釈を付けた手
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotatedMove {
+    /// 手数（パスを含む、1から）
+    pub ply: usize,
+    pub color: DiskColor,
+    /// 打った手の標準座標表記（例: `f5`）
+    pub notation: String,
+    /// 打った手の評価値（打った側から見た値）
+    pub score: i32,
+    /// 最善手の標準座標表記
+    pub best_move: String,
+    /// 最善手の評価値（打った側から見た値）
+    pub best_score: i32,
+    /// 最善手との評価値の差
+    pub loss: i32,
+    /// 終局まで読み切った値か（trueの場合は評価値が石差）
+    pub exact: bool,
+    pub annotation: Annotation,
+}
+
+/// 対局の解析結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisReport {
+    pub game_id: String,
+    pub black: String,
+    pub white: String,
+    /// 石を置いた手ごとの注釈
+    pub moves: Vec<AnnotatedMove>,
+    /// 各手を打った後の黒から見た評価値の推移（`moves`と同じ順）
+    pub graph: Vec<i32>,
+    /// 主な疑問手と悪手（悪手を先に、それぞれ損失の大きい順）
+    pub key_mistakes: Vec<AnnotatedMove>,
+}
+
+/// 最善手との差から手の評価を決める
+fn annotate(best_score: i32, score: i32, exact: bool) -> Annotation {
+    let loss = best_score - score;
+    let (mistake, blunder) = if exact {
+        (MISTAKE_DISCS, BLUNDER_DISCS)
+    } else {
+        (MISTAKE_EVALUATION, BLUNDER_EVALUATION)
+    };
+
+    // 勝ちを逃した手は損失によらず悪手
+    let threw_win = if exact {
+        best_score > 0 && score <= 0
+    } else {
+        best_score >= WIN_SCORE && score < WIN_SCORE
+    };
+
+    if loss <= 0 {
+        Annotation::Best
+    } else if loss >= blunder || threw_win {
+        Annotation::Blunder
+    } else if loss >= mistake {
+        Annotation::Mistake
+    } else {
+        Annotation::Good
+    }
+}
+
+/// 対局を初期局面から再生し、石を置いた各手を評価する
+///
+/// 1局面あたり`budget`の時間まで読むため、手数に比例した時間がかかる
+pub fn analyze(game: &Game, budget: Duration) -> AnalysisReport {
+    let variant = game.config().variant;
+    let mut board = *game.initial_board();
+    let mut moves = Vec::new();
+
+    for (index, game_move) in game.move_history().iter().enumerate() {
+        let MoveAction::Place { x, y } = game_move.action else {
+            continue;
+        };
+        let color = game_move.color;
+
+        let (ranked, exact) = ai::rank_moves(&board, color, variant, budget);
+        let played = ranked.iter().find(|mv| mv.square == [x, y]);
+        if let (Some(best), Some(played)) = (ranked.first(), played) {
+            moves.push(AnnotatedMove {
+                ply: index + 1,
+                color,
+                notation: notation::format_square(x, y),
+                score: played.score,
+                best_move: best.notation.clone(),
+                best_score: best.score,
+                loss: best.score - played.score,
+                exact,
+                annotation: annotate(best.score, played.score, exact),
+            });
+        }
+
+        board.play(color, x, y);
+    }
+
+    let graph = moves
+        .iter()
+        .map(|mv| match mv.color {
+            DiskColor::Black => mv.score,
+            DiskColor::White => -mv.score,
+        })
+        .collect();
+
+    let mut key_mistakes: Vec<AnnotatedMove> = moves
+        .iter()
+        .filter(|mv| matches!(mv.annotation, Annotation::Mistake | Annotation::Blunder))
+        .cloned()
+        .collect();
+    // 読み切った石差と途中までの評価値は単位が異なるため、まず評価の重さで並べる
+    key_mistakes.sort_by_key(|mv| std::cmp::Reverse((mv.annotation, mv.loss)));
+    key_mistakes.truncate(KEY_MISTAKES);
+
+    AnalysisReport {
+        game_id: game.game_id.clone(),
+        black: game.black_player_name.clone(),
+        white: game.white_player_name.clone(),
+        moves,
+        graph,
+        key_mistakes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_losses_use_disc_thresholds() {
+        assert_eq!(annotate(10, 10, true), Annotation::Best);
+        assert_eq!(annotate(10, 12, true), Annotation::Best);
+        assert_eq!(annotate(10, 7, true), Annotation::Good);
+        assert_eq!(annotate(10, 6, true), Annotation::Mistake);
+        assert_eq!(annotate(20, 11, true), Annotation::Mistake);
+        assert_eq!(annotate(20, 10, true), Annotation::Blunder);
+        assert_eq!(annotate(0, -2, true), Annotation::Good);
+    }
+
+    #[test]
+    fn evaluation_losses_use_evaluation_thresholds() {
+        assert_eq!(annotate(50, 50, false), Annotation::Best);
+        assert_eq!(annotate(50, 11, false), Annotation::Good);
+        assert_eq!(annotate(50, 10, false), Annotation::Mistake);
+        assert_eq!(annotate(50, -49, false), Annotation::Mistake);
+        assert_eq!(annotate(50, -50, false), Annotation::Blunder);
+        // 読み切っていない評価値の差は石差の基準では数えない
+        assert_eq!(annotate(10, 0, false), Annotation::Good);
+    }
+
+    #[test]
+    fn throwing_a_win_is_a_blunder() {
+        assert_eq!(annotate(2, 0, true), Annotation::Blunder);
+        assert_eq!(annotate(2, -1, true), Annotation::Blunder);
+        assert_eq!(annotate(2, 1, true), Annotation::Good);
+        assert_eq!(
+            annotate(WIN_SCORE, WIN_SCORE - 1, false),
+            Annotation::Blunder
+        );
+        assert_eq!(annotate(WIN_SCORE + 5, WIN_SCORE, false), Annotation::Good);
+    }
+
+    #[test]
+    fn analyzes_a_scripted_game() {
+        // 空き8マスから黒が先に打ち、8手目で白がパスする
+        let mut game = Game::from_position(
+            "analysis".to_string(),
+            "black-id".to_string(),
+            "black".to_string(),
+            "white-id".to_string(),
+            "white".to_string(),
+            "OO-XXXXXOOOOXXX-OOOXO-X-OOOOOXOOOXOOXO--XXOXXOOOX-XOOOOO-XXXXOOO",
+            DiskColor::Black,
+        )
+        .unwrap();
+        for notation in ["h5", "b7", "f3", "g5", "a8", "h3", "h2", "c1"] {
+            let player_id = game.player_id(game.current_color()).to_string();
+            game.make_move_notation(&player_id, notation).unwrap();
+        }
+        assert!(game.is_over());
+        assert_eq!(game.move_history()[7].action, MoveAction::Pass);
+
+        let report = analyze(&game, Duration::from_secs(1));
+
+        // パスは評価しないが手数には数える
+        let plies: Vec<usize> = report.moves.iter().map(|mv| mv.ply).collect();
+        assert_eq!(plies, [1, 2, 3, 4, 5, 6, 7, 9]);
+        assert!(report.moves.iter().all(|mv| mv.exact));
+
+        // h5は読み切りで黒-6のところ-20、g5は白の勝ちを逃す手
+        let h5 = &report.moves[0];
+        assert_eq!(
+            (h5.best_move.as_str(), h5.best_score, h5.score),
+            ("f3", -6, -20)
+        );
+        assert_eq!(h5.annotation, Annotation::Blunder);
+        let g5 = &report.moves[3];
+        assert_eq!(
+            (g5.best_move.as_str(), g5.best_score, g5.score),
+            ("a8", 20, -4)
+        );
+        assert_eq!(g5.annotation, Annotation::Blunder);
+
+        // 評価値の推移は黒から見た値
+        assert_eq!(report.graph, [-20, -16, -20, 4, 4, 10, 10, 10]);
+        for (mv, &value) in report.moves.iter().zip(&report.graph) {
+            let expected = match mv.color {
+                DiskColor::Black => mv.score,
+                DiskColor::White => -mv.score,
+            };
+            assert_eq!(value, expected);
+        }
+
+        // 悪手を損失の大きい順に、続いて疑問手を損失の大きい順に並べる
+        let key_mistakes: Vec<&str> = report
+            .key_mistakes
+            .iter()
+            .map(|mv| mv.notation.as_str())
+            .collect();
+        assert_eq!(key_mistakes, ["g5", "h5", "h3", "b7", "f3"]);
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::game::Game;
+use crate::{app_log, info_log};
+
+/// 保管するゲームの最大数（超えた分は古いものから捨てる）
+const MAX_ARCHIVED_GAMES: usize = 10_000;
+
+/// アーカイブされたゲーム
+pub struct ArchivedGame {
+    /// ゲーム
+    pub game: Game,
+    /// 棋譜を読み込んだユーザー名（サーバー上の対局の場合はNone）
+    pub imported_by: Option<String>,
+}
+
+impl ArchivedGame {
+    /// ユーザーがこのゲームを参照できるか
+    ///
+    /// 読み込んだ棋譜の対局者名はファイルに書かれたままのため、読み込んだユーザーだけが参照できる
+    pub fn is_accessible_by(&self, username: &str) -> bool {
+        match &self.imported_by {
+            Some(importer) => importer == username,
+            None => {
+                self.game.black_player_name == username || self.game.white_player_name == username
+            }
+        }
+    }
+}
+
+/// 終了したゲームと読み込んだ棋譜の保管庫
+pub struct GameArchive {
+    /// ゲームID -> アーカイブされたゲーム
+    games: HashMap<String, ArchivedGame>,
+    /// 保管した順番
+    order: VecDeque<String>,
+    /// 保管するゲームの最大数
+    limit: usize,
+}
+
+impl Default for GameArchive {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameArchive {
+    pub fn new() -> Self {
+        Self::with_limit(MAX_ARCHIVED_GAMES)
+    }
+
+    /// 保管するゲームの最大数を指定して作成
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            games: HashMap::new(),
+            order: VecDeque::new(),
+            limit,
+        }
+    }
+
+    /// ゲームを保管（最大数を超えた場合は最も古いゲームを捨てる）
+    pub fn store(&mut self, game: Game, imported_by: Option<String>) {
+        let game_id = game.game_id.clone();
+        info_log!("Game archived: {}", game_id);
+
+        if self
+            .games
+            .insert(game_id.clone(), ArchivedGame { game, imported_by })
+            .is_none()
+        {
+            self.order.push_back(game_id);
+        }
+        while self.order.len() > self.limit {
+            if let Some(oldest) = self.order.pop_front() {
+                self.games.remove(&oldest);
+                info_log!("Game evicted from archive: {}", oldest);
+            }
+        }
+    }
+
+    /// 保管しているゲームの数
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    /// 保管しているゲームがないか
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    /// ゲームを取得
+    pub fn get(&self, game_id: &str) -> Option<&ArchivedGame> {
+        self.games.get(game_id)
+    }
+
+    /// ユーザーが参照できるゲームを新しい順に取得
+    pub fn games_for_user(&self, username: &str) -> Vec<&Game> {
+        self.order
+            .iter()
+            .rev()
+            .filter_map(|id| self.games.get(id))
+            .filter(|archived| archived.is_accessible_by(username))
+            .map(|archived| &archived.game)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameConfig;
+
+    fn game(id: &str) -> Game {
+        Game::new(
+            id.to_string(),
+            "black-id".to_string(),
+            "alice".to_string(),
+            "white-id".to_string(),
+            "bob".to_string(),
+            GameConfig::default(),
+            None,
+        )
+    }
+
+    #[test]
+    fn imported_games_are_visible_only_to_importer() {
+        let mut archive = GameArchive::new();
+        archive.store(game("played"), None);
+        archive.store(game("imported"), Some("carol".to_string()));
+
+        let ids = |username| {
+            archive
+                .games_for_user(username)
+                .iter()
+                .map(|game| game.game_id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids("alice"), vec!["played"]);
+        assert_eq!(ids("bob"), vec!["played"]);
+        assert_eq!(ids("carol"), vec!["imported"]);
+        assert!(ids("dave").is_empty());
+    }
+
+    #[test]
+    fn oldest_games_are_evicted_past_the_limit() {
+        let mut archive = GameArchive::with_limit(2);
+        archive.store(game("first"), None);
+        archive.store(game("second"), None);
+        // 同じゲームを保管し直しても数えない
+        archive.store(game("second"), None);
+        assert_eq!(archive.len(), 2);
+
+        archive.store(game("third"), None);
+        assert_eq!(archive.len(), 2);
+        assert!(archive.get("first").is_none());
+        let ids: Vec<&str> = archive
+            .games_for_user("alice")
+            .iter()
+            .map(|game| game.game_id.as_str())
+            .collect();
+        assert_eq!(ids, ["third", "second"]);
+    }
+}
@@ -0,0 +1,260 @@
+//! # Perft
+//!
+//! 指定した局面から深さNまでの末端局面の数を数え、着手生成の正しさと速度を確認する
+//!
+//! 公開されている`Game`のAPIだけを使って手を進めるため、着手判定や反転処理を
+//! 書き換えた際の検証に使える。通常の初期配置では既知の値と比較する
+//!
+//! - パスは1手として数える（打てない側の手番で深さを1つ消費する）
+//! - 深さNに達する前に終局した局面は末端局面として1つ数える
+//!
+//! ```text
+//! cargo run --release --bin perft -- 9
+//! cargo run --release --bin perft -- 6 --size 10
+//! cargo run --release --bin perft -- 5 --board "<盤面文字列>" --side white --divide
+//! ```
+
+use std::env;
+use std::process::ExitCode;
+use std::time::Instant;
+
+use server::bitboard::DEFAULT_BOARD_SIZE;
+use server::game::{DiskColor, Game, GameConfig};
+use server::notation;
+
+/// 8x8の初期配置からの既知の値（深さ1から順）
+const REFERENCE: [u64; 9] = [4, 12, 56, 244, 1396, 8200, 55092, 390216, 3005288];
+/// 深さを指定しない場合の深さ
+const DEFAULT_DEPTH: u32 = 8;
+
+const USAGE: &str = "Usage: perft [DEPTH] [--size N] [--board BOARD --side black|white] [--divide]";
+
+/// コマンドライン引数
+struct Options {
+    depth: u32,
+    board_size: usize,
+    board: Option<String>,
+    side: DiskColor,
+    divide: bool,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        depth: DEFAULT_DEPTH,
+        board_size: DEFAULT_BOARD_SIZE,
+        board: None,
+        side: DiskColor::Black,
+        divide: false,
+    };
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let value = args.next().ok_or("--size needs a value")?;
+                options.board_size = value
+                    .parse()
+                    .map_err(|_| format!("Invalid board size: {}", value))?;
+            }
+            "--board" => {
+                options.board = Some(args.next().ok_or("--board needs a value")?);
+            }
+            "--side" => {
+                options.side = match args.next().as_deref() {
+                    Some("black") => DiskColor::Black,
+                    Some("white") => DiskColor::White,
+                    _ => return Err("--side must be black or white".to_string()),
+                };
+            }
+            "--divide" => options.divide = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => {
+                options.depth = arg
+                    .parse()
+                    .map_err(|_| format!("Invalid depth: {}\n{}", arg, USAGE))?;
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+/// 開始局面のゲームを作成
+fn start_game(options: &Options) -> Result<Game, String> {
+    let (black, white) = (DiskColor::Black.to_string(), DiskColor::White.to_string());
+
+    match &options.board {
+        Some(board) => Game::from_position(
+            "perft".to_string(),
+            black.clone(),
+            black,
+            white.clone(),
+            white,
+            board,
+            options.side,
+        ),
+        None => {
+            let config = GameConfig {
+                board_size: options.board_size,
+                ..GameConfig::default()
+            };
+            config.validate()?;
+            Ok(Game::new(
+                "perft".to_string(),
+                black.clone(),
+                black,
+                white.clone(),
+                white,
+                config,
+                None,
+            ))
+        }
+    }
+}
+
+/// 手番側の合法手をそれぞれ打った後の局面と、その手で消費した深さの一覧
+fn children(game: &Game) -> Vec<((usize, usize), Game, u32)> {
+    let player_id = game.player_id(game.current_color()).to_string();
+    let before = game.move_history().len();
+
+    game.legal_moves()
+        .into_iter()
+        .map(|(x, y)| {
+            let mut child = game.clone();
+            child
+                .make_move(&player_id, x, y)
+                .expect("Legal move was rejected");
+
+            // 手の後に自動で記録されたパスも1手として数える
+            let plies = (child.move_history().len() - before) as u32;
+            ((x, y), child, plies)
+        })
+        .collect()
+}
+
+/// 深さ`depth`までの末端局面の数
+fn perft(game: &Game, depth: u32) -> u64 {
+    if depth == 0 || game.is_over() {
+        return 1;
+    }
+
+    children(game)
+        .iter()
+        .map(|(_, child, plies)| perft(child, depth.saturating_sub(*plies)))
+        .sum()
+}
+
+fn main() -> ExitCode {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let game = match start_game(&options) {
+        Ok(game) => game,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // 既知の値と比較できるのは8x8の通常の初期配置のみ
+    let reference =
+        (options.board.is_none() && options.board_size == DEFAULT_BOARD_SIZE).then_some(&REFERENCE);
+    let mut mismatch = false;
+
+    println!("{}", notation::format_board_string(game.board()));
+    println!("{} to move", game.current_color());
+
+    if options.divide {
+        let mut total = 0;
+        for ((x, y), child, plies) in children(&game) {
+            let nodes = perft(&child, options.depth.saturating_sub(plies));
+            println!("{}: {}", notation::format_square(x, y), nodes);
+            total += nodes;
+        }
+        println!("total: {}", total);
+        return ExitCode::SUCCESS;
+    }
+
+    println!(
+        "{:>5} {:>14} {:>10} {:>14}  check",
+        "depth", "nodes", "seconds", "nodes/sec"
+    );
+    for depth in 1..=options.depth {
+        let started = Instant::now();
+        let nodes = perft(&game, depth);
+        let seconds = started.elapsed().as_secs_f64();
+
+        let check = match reference.and_then(|counts| counts.get(depth as usize - 1)) {
+            Some(&expected) if expected == nodes => "ok".to_string(),
+            Some(&expected) => {
+                mismatch = true;
+                format!("MISMATCH (expected {})", expected)
+            }
+            None => "-".to_string(),
+        };
+        println!(
+            "{:>5} {:>14} {:>10.3} {:>14.0}  {}",
+            depth,
+            nodes,
+            seconds,
+            nodes as f64 / seconds.max(f64::EPSILON),
+            check
+        );
+    }
+
+    if mismatch {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_position_matches_reference() {
+        let options = Options {
+            depth: 6,
+            board_size: DEFAULT_BOARD_SIZE,
+            board: None,
+            side: DiskColor::Black,
+            divide: false,
+        };
+        let game = start_game(&options).unwrap();
+
+        for depth in 1..=options.depth {
+            assert_eq!(
+                perft(&game, depth),
+                REFERENCE[depth as usize - 1],
+                "depth {}",
+                depth
+            );
+        }
+    }
+
+    #[test]
+    fn divide_sums_to_total() {
+        let options = Options {
+            depth: 4,
+            board_size: DEFAULT_BOARD_SIZE,
+            board: Some(notation::format_board_string(
+                &server::bitboard::Board::initial(8),
+            )),
+            side: DiskColor::Black,
+            divide: true,
+        };
+        let game = start_game(&options).unwrap();
+
+        let total: u64 = children(&game)
+            .iter()
+            .map(|(_, child, plies)| perft(child, options.depth - plies))
+            .sum();
+        assert_eq!(total, REFERENCE[3]);
+    }
+}
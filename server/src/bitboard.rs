@@ -0,0 +1,749 @@
+//! # ビットボード
+//!
+//! 盤面を黒石・白石それぞれ1つのビット集合で表現し、
+//! シフトとマスクで合法手の生成と反転石の計算を行う
+//!
+//! マス`(x, y)`はビット`y * size + x`に対応する（a1 = ビット0）
+//!
+//! 石を置けない穴のマスは別のビット集合で持ち、空きマスから除外する。
+//! 穴には石が置かれないため、石の連続を伸ばす走査は盤の端と同じく穴で止まる
+//!
+//! ## 型
+//!
+//! - `Bits`:     ビット集合として扱える型（8x8以下は`u64`、それ以上は`WideBits`）
+//! - `Bitboard`: ビット集合の型ごとの盤面
+//! - `Board`:    盤面サイズに応じて`Bitboard`を切り替える盤面
+
+use lazy_static::lazy_static;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, Not, Shl, Shr};
+
+use crate::game::DiskColor;
+
+/// 対応している盤面サイズ
+pub const SUPPORTED_BOARD_SIZES: [usize; 4] = [6, 8, 10, 12];
+/// 標準の盤面サイズ
+pub const DEFAULT_BOARD_SIZE: usize = 8;
+/// `u64`で扱える最大の盤面サイズ
+const NARROW_MAX_SIZE: usize = 8;
+/// `WideBits`で扱える最大の盤面サイズ
+const WIDE_MAX_SIZE: usize = 12;
+
+/// ビット集合として扱える型
+pub trait Bits:
+    Copy
+    + Eq
+    + Hash
+    + Debug
+    + Default
+    + Send
+    + Sync
+    + 'static
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+    + Not<Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+    + BitAndAssign
+    + BitOrAssign
+{
+    /// 空集合
+    const ZERO: Self;
+
+    /// `index`番目のビットのみを含む集合
+    fn bit(index: usize) -> Self;
+
+    /// 含まれるビットの数
+    fn count(self) -> u32;
+
+    /// 最下位ビットの位置（空集合ならNone）
+    fn lowest(self) -> Option<usize>;
+
+    /// 最下位ビットを取り除いた集合
+    fn without_lowest(self) -> Self;
+
+    /// 盤面サイズに対応する形状情報
+    fn geometry(size: usize) -> &'static Geometry<Self>;
+
+    /// 空集合かどうか
+    #[inline]
+    fn is_zero(self) -> bool {
+        self == Self::ZERO
+    }
+
+    /// `index`番目のビットを含むか
+    #[inline]
+    fn has(self, index: usize) -> bool {
+        !(self & Self::bit(index)).is_zero()
+    }
+
+    /// 含まれるビットの位置を列挙
+    fn indices(self) -> BitIndices<Self> {
+        BitIndices(self)
+    }
+}
+
+/// ビット位置のイテレータ
+pub struct BitIndices<B>(B);
+
+impl<B: Bits> Iterator for BitIndices<B> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let index = self.0.lowest()?;
+        self.0 = self.0.without_lowest();
+        Some(index)
+    }
+}
+
+impl Bits for u64 {
+    const ZERO: Self = 0;
+
+    #[inline]
+    fn bit(index: usize) -> Self {
+        1 << index
+    }
+
+    #[inline]
+    fn count(self) -> u32 {
+        self.count_ones()
+    }
+
+    #[inline]
+    fn lowest(self) -> Option<usize> {
+        (self != 0).then(|| self.trailing_zeros() as usize)
+    }
+
+    #[inline]
+    fn without_lowest(self) -> Self {
+        self & self.wrapping_sub(1)
+    }
+
+    fn geometry(size: usize) -> &'static Geometry<Self> {
+        lazy_static! {
+            static ref GEOMETRIES: Vec<Geometry<u64>> =
+                (0..=NARROW_MAX_SIZE).map(Geometry::new).collect();
+        }
+        &GEOMETRIES[size]
+    }
+}
+
+/// 64マスを超える盤面用のビット集合（最大192マス）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct WideBits([u64; 3]);
+
+impl BitAnd for WideBits {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self {
+        let [a, b, c] = self.0;
+        let [x, y, z] = rhs.0;
+        WideBits([a & x, b & y, c & z])
+    }
+}
+
+impl BitOr for WideBits {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        let [a, b, c] = self.0;
+        let [x, y, z] = rhs.0;
+        WideBits([a | x, b | y, c | z])
+    }
+}
+
+impl BitXor for WideBits {
+    type Output = Self;
+
+    #[inline]
+    fn bitxor(self, rhs: Self) -> Self {
+        let [a, b, c] = self.0;
+        let [x, y, z] = rhs.0;
+        WideBits([a ^ x, b ^ y, c ^ z])
+    }
+}
+
+impl Not for WideBits {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self {
+        let [a, b, c] = self.0;
+        WideBits([!a, !b, !c])
+    }
+}
+
+impl BitAndAssign for WideBits {
+    #[inline]
+    fn bitand_assign(&mut self, rhs: Self) {
+        *self = *self & rhs;
+    }
+}
+
+impl BitOrAssign for WideBits {
+    #[inline]
+    fn bitor_assign(&mut self, rhs: Self) {
+        *self = *self | rhs;
+    }
+}
+
+/// シフト量は1ワード未満（盤面の方向シフトでは最大13）
+impl Shl<u32> for WideBits {
+    type Output = Self;
+
+    #[inline]
+    fn shl(self, amount: u32) -> Self {
+        if amount == 0 {
+            return self;
+        }
+        let [a, b, c] = self.0;
+        let carry = 64 - amount;
+        WideBits([
+            a << amount,
+            (b << amount) | (a >> carry),
+            (c << amount) | (b >> carry),
+        ])
+    }
+}
+
+/// シフト量は1ワード未満（盤面の方向シフトでは最大13）
+impl Shr<u32> for WideBits {
+    type Output = Self;
+
+    #[inline]
+    fn shr(self, amount: u32) -> Self {
+        if amount == 0 {
+            return self;
+        }
+        let [a, b, c] = self.0;
+        let carry = 64 - amount;
+        WideBits([
+            (a >> amount) | (b << carry),
+            (b >> amount) | (c << carry),
+            c >> amount,
+        ])
+    }
+}
+
+impl Bits for WideBits {
+    const ZERO: Self = WideBits([0; 3]);
+
+    #[inline]
+    fn bit(index: usize) -> Self {
+        let mut words = [0; 3];
+        words[index / 64] = 1 << (index % 64);
+        WideBits(words)
+    }
+
+    #[inline]
+    fn count(self) -> u32 {
+        self.0.iter().map(|word| word.count_ones()).sum()
+    }
+
+    #[inline]
+    fn lowest(self) -> Option<usize> {
+        self.0
+            .iter()
+            .enumerate()
+            .find(|(_, word)| **word != 0)
+            .map(|(i, word)| i * 64 + word.trailing_zeros() as usize)
+    }
+
+    #[inline]
+    fn without_lowest(self) -> Self {
+        let mut words = self.0;
+        if let Some(word) = words.iter_mut().find(|word| **word != 0) {
+            *word &= *word - 1;
+        }
+        WideBits(words)
+    }
+
+    fn geometry(size: usize) -> &'static Geometry<Self> {
+        lazy_static! {
+            static ref GEOMETRIES: Vec<Geometry<WideBits>> =
+                (0..=WIDE_MAX_SIZE).map(Geometry::new).collect();
+        }
+        &GEOMETRIES[size]
+    }
+}
+
+/// 盤面サイズごとのシフト量とマスク
+#[derive(Debug)]
+pub struct Geometry<B> {
+    /// 盤面の一辺の長さ
+    pub size: usize,
+    /// 盤面上の全マス
+    pub full: B,
+    /// 8方向のシフト（正は左シフト、負は右シフト）と回り込み防止マスク
+    shifts: [(i32, B); 8],
+}
+
+impl<B: Bits> Geometry<B> {
+    fn new(size: usize) -> Self {
+        let mut full = B::ZERO;
+        let mut not_first_file = B::ZERO;
+        let mut not_last_file = B::ZERO;
+
+        for y in 0..size {
+            for x in 0..size {
+                let bit = B::bit(y * size + x);
+                full |= bit;
+                if x != 0 {
+                    not_first_file |= bit;
+                }
+                if x + 1 != size {
+                    not_last_file |= bit;
+                }
+            }
+        }
+
+        let n = size as i32;
+        Self {
+            size,
+            full,
+            shifts: [
+                (1, not_first_file),     // 右
+                (-1, not_last_file),     // 左
+                (n, full),               // 下
+                (-n, full),              // 上
+                (n + 1, not_first_file), // 右下
+                (n - 1, not_last_file),  // 左下
+                (1 - n, not_first_file), // 右上
+                (-1 - n, not_last_file), // 左上
+            ],
+        }
+    }
+
+    /// 指定方向に1マス分ずらす
+    #[inline]
+    fn shift(bits: B, amount: i32, mask: B) -> B {
+        if amount > 0 {
+            (bits << amount as u32) & mask
+        } else {
+            (bits >> (-amount) as u32) & mask
+        }
+    }
+
+    /// 手番側から見た合法手の集合（`holes`のマスには置けない）
+    #[inline]
+    pub fn legal_moves(&self, player: B, opponent: B, holes: B) -> B {
+        let empty = self.full & !(player | opponent | holes);
+        let mut moves = B::ZERO;
+
+        for (amount, mask) in self.shifts {
+            // 自石に隣接する相手石の連続を伸ばしていく
+            let mut line = Self::shift(player, amount, mask) & opponent;
+            for _ in 0..self.size.saturating_sub(3) {
+                line |= Self::shift(line, amount, mask) & opponent;
+            }
+            // 連続の先が空きマスなら合法手
+            moves |= Self::shift(line, amount, mask) & empty;
+        }
+
+        moves
+    }
+
+    /// `square`に石を置いたときに反転する石の集合
+    #[inline]
+    pub fn flips(&self, player: B, opponent: B, square: B) -> B {
+        let mut flipped = B::ZERO;
+
+        for (amount, mask) in self.shifts {
+            let mut line = B::ZERO;
+            let mut cursor = Self::shift(square, amount, mask);
+            // 相手石が続く限り反転候補に追加
+            while !(cursor & opponent).is_zero() {
+                line |= cursor;
+                cursor = Self::shift(cursor, amount, mask);
+            }
+            // 自石で挟めていれば反転確定
+            if !(cursor & player).is_zero() {
+                flipped |= line;
+            }
+        }
+
+        flipped
+    }
+}
+
+/// 黒石・白石のビットボード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bitboard<B> {
+    black: B,
+    white: B,
+    /// 石を置けない穴のマス
+    holes: B,
+    size: usize,
+}
+
+impl<B: Bits> Bitboard<B> {
+    /// 石のない盤面
+    pub fn empty(size: usize) -> Self {
+        Self {
+            black: B::ZERO,
+            white: B::ZERO,
+            holes: B::ZERO,
+            size,
+        }
+    }
+
+    /// 盤面の一辺の長さ
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// 盤面サイズに対応する形状情報
+    pub fn geometry(&self) -> &'static Geometry<B> {
+        B::geometry(self.size)
+    }
+
+    /// 座標をビット位置に変換
+    #[inline]
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        y * self.size + x
+    }
+
+    /// ビット位置を座標に変換
+    #[inline]
+    pub fn coordinates(&self, index: usize) -> (usize, usize) {
+        (index % self.size, index / self.size)
+    }
+
+    /// 指定した色の石の集合
+    #[inline]
+    pub fn discs(&self, color: DiskColor) -> B {
+        match color {
+            DiskColor::Black => self.black,
+            DiskColor::White => self.white,
+        }
+    }
+
+    /// 穴のマスの集合
+    #[inline]
+    pub fn holes(&self) -> B {
+        self.holes
+    }
+
+    /// 空きマスの集合（穴を除く）
+    #[inline]
+    pub fn empties(&self) -> B {
+        self.geometry().full & !(self.black | self.white | self.holes)
+    }
+
+    /// マスの状態を取得
+    pub fn get(&self, index: usize) -> Option<DiskColor> {
+        if self.black.has(index) {
+            Some(DiskColor::Black)
+        } else if self.white.has(index) {
+            Some(DiskColor::White)
+        } else {
+            None
+        }
+    }
+
+    /// マスの状態を設定
+    pub fn set(&mut self, index: usize, cell: Option<DiskColor>) {
+        let bit = B::bit(index);
+        self.black &= !bit;
+        self.white &= !bit;
+        match cell {
+            Some(DiskColor::Black) => self.black |= bit,
+            Some(DiskColor::White) => self.white |= bit,
+            None => {}
+        }
+    }
+
+    /// マスを穴にする（置かれていた石は取り除く）
+    pub fn set_hole(&mut self, index: usize) {
+        self.set(index, None);
+        self.holes |= B::bit(index);
+    }
+
+    /// 指定した色の合法手の集合
+    #[inline]
+    pub fn legal_moves(&self, color: DiskColor) -> B {
+        self.geometry()
+            .legal_moves(self.discs(color), self.discs(color.opposite()), self.holes)
+    }
+
+    /// `index`に置いたときに反転する石の集合（置けない場合は空集合）
+    #[inline]
+    pub fn flips(&self, color: DiskColor, index: usize) -> B {
+        let bit = B::bit(index);
+        if (self.empties() & bit).is_zero() {
+            return B::ZERO;
+        }
+        self.geometry()
+            .flips(self.discs(color), self.discs(color.opposite()), bit)
+    }
+
+    /// `index`に石を置き、`flipped`を反転させる
+    #[inline]
+    pub fn apply(&mut self, color: DiskColor, index: usize, flipped: B) {
+        let placed = B::bit(index) | flipped;
+        match color {
+            DiskColor::Black => {
+                self.black |= placed;
+                self.white &= !flipped;
+            }
+            DiskColor::White => {
+                self.white |= placed;
+                self.black &= !flipped;
+            }
+        }
+    }
+
+    /// `index`に石を置いて反転させ、反転した石の集合を返す
+    ///
+    /// 反転する石がない場合は盤面を変更せず空集合を返す
+    #[inline]
+    pub fn play(&mut self, color: DiskColor, index: usize) -> B {
+        let flipped = self.flips(color, index);
+        if !flipped.is_zero() {
+            self.apply(color, index, flipped);
+        }
+        flipped
+    }
+}
+
+/// 盤面サイズに応じたビットボード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Board {
+    /// 8x8以下の盤面
+    Narrow(Bitboard<u64>),
+    /// 8x8より大きい盤面
+    Wide(Bitboard<WideBits>),
+}
+
+/// 盤面の種類に関わらず同じ処理を行う
+macro_rules! dispatch {
+    ($board:expr, $inner:ident => $body:expr) => {
+        match $board {
+            Board::Narrow($inner) => $body,
+            Board::Wide($inner) => $body,
+        }
+    };
+}
+
+impl Board {
+    /// 石のない盤面
+    pub fn empty(size: usize) -> Self {
+        if size <= NARROW_MAX_SIZE {
+            Board::Narrow(Bitboard::empty(size))
+        } else {
+            Board::Wide(Bitboard::empty(size))
+        }
+    }
+
+    /// 中央に4石を置いた初期配置の盤面
+    pub fn initial(size: usize) -> Self {
+        let mut board = Self::empty(size);
+        let low = size / 2 - 1;
+        let high = size / 2;
+        board.set(low, low, Some(DiskColor::White));
+        board.set(high, low, Some(DiskColor::Black));
+        board.set(low, high, Some(DiskColor::Black));
+        board.set(high, high, Some(DiskColor::White));
+        board
+    }
+
+    /// 盤面の一辺の長さ
+    pub fn size(&self) -> usize {
+        dispatch!(self, board => board.size())
+    }
+
+    /// マスの状態を取得
+    pub fn get(&self, x: usize, y: usize) -> Option<DiskColor> {
+        dispatch!(self, board => board.get(board.index(x, y)))
+    }
+
+    /// マスの状態を設定
+    pub fn set(&mut self, x: usize, y: usize, cell: Option<DiskColor>) {
+        dispatch!(self, board => board.set(board.index(x, y), cell))
+    }
+
+    /// マスが穴か
+    pub fn is_hole(&self, x: usize, y: usize) -> bool {
+        dispatch!(self, board => board.holes().has(board.index(x, y)))
+    }
+
+    /// マスを穴にする（置かれていた石は取り除く）
+    pub fn set_hole(&mut self, x: usize, y: usize) {
+        dispatch!(self, board => board.set_hole(board.index(x, y)))
+    }
+
+    /// 穴のマスの座標一覧
+    pub fn holes(&self) -> Vec<(usize, usize)> {
+        dispatch!(self, board => board
+            .holes()
+            .indices()
+            .map(|index| board.coordinates(index))
+            .collect())
+    }
+
+    /// 指定した色の石の数
+    pub fn count(&self, color: DiskColor) -> usize {
+        dispatch!(self, board => board.discs(color).count() as usize)
+    }
+
+    /// 空きマスの数（穴を除く）
+    pub fn empty_count(&self) -> usize {
+        dispatch!(self, board => board.empties().count() as usize)
+    }
+
+    /// 指定した色に合法手があるか
+    pub fn has_legal_move(&self, color: DiskColor) -> bool {
+        dispatch!(self, board => !board.legal_moves(color).is_zero())
+    }
+
+    /// 指定した色の合法手の座標一覧
+    pub fn legal_moves(&self, color: DiskColor) -> Vec<(usize, usize)> {
+        dispatch!(self, board => board
+            .legal_moves(color)
+            .indices()
+            .map(|index| board.coordinates(index))
+            .collect())
+    }
+
+    /// `(x, y)`に石を置いて反転させ、反転した石の座標一覧を返す
+    ///
+    /// 反転する石がない場合は盤面を変更せず空の一覧を返す
+    pub fn play(&mut self, color: DiskColor, x: usize, y: usize) -> Vec<(usize, usize)> {
+        dispatch!(self, board => {
+            let flipped = board.play(color, board.index(x, y));
+            flipped
+                .indices()
+                .map(|index| board.coordinates(index))
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const DIRECTIONS: [(isize, isize); 8] = [
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+    ];
+
+    /// 石と穴を無作為に置いた盤面（`hole_rate`の割合で穴にする）
+    fn random_board<R: Rng>(size: usize, hole_rate: f64, rng: &mut R) -> Board {
+        let mut board = Board::empty(size);
+        for y in 0..size {
+            for x in 0..size {
+                if rng.gen_bool(hole_rate) {
+                    board.set_hole(x, y);
+                    continue;
+                }
+                let cell = match rng.gen_range(0..3) {
+                    0 => Some(DiskColor::Black),
+                    1 => Some(DiskColor::White),
+                    _ => None,
+                };
+                board.set(x, y, cell);
+            }
+        }
+        board
+    }
+
+    /// マスを1つずつ辿って求めた`(x, y)`に打った場合に反転する石
+    fn reference_flips(board: &Board, color: DiskColor, x: usize, y: usize) -> Vec<(usize, usize)> {
+        if board.get(x, y).is_some() || board.is_hole(x, y) {
+            return Vec::new();
+        }
+
+        let size = board.size() as isize;
+        let mut flips = Vec::new();
+        for (dx, dy) in DIRECTIONS {
+            let mut line = Vec::new();
+            let (mut cx, mut cy) = (x as isize + dx, y as isize + dy);
+            while (0..size).contains(&cx) && (0..size).contains(&cy) {
+                match board.get(cx as usize, cy as usize) {
+                    Some(c) if c == color.opposite() => line.push((cx as usize, cy as usize)),
+                    Some(_) => {
+                        flips.append(&mut line);
+                        break;
+                    }
+                    None => break,
+                }
+                cx += dx;
+                cy += dy;
+            }
+        }
+        flips.sort();
+        flips
+    }
+
+    fn check_against_reference(hole_rate: f64) {
+        let mut rng = StdRng::seed_from_u64(0);
+        for size in SUPPORTED_BOARD_SIZES {
+            for _ in 0..100 {
+                let board = random_board(size, hole_rate, &mut rng);
+                for color in [DiskColor::Black, DiskColor::White] {
+                    let mut expected = Vec::new();
+                    for y in 0..size {
+                        for x in 0..size {
+                            let flips = reference_flips(&board, color, x, y);
+                            let mut played = board;
+                            let mut actual = played.play(color, x, y);
+                            actual.sort();
+                            assert_eq!(
+                                actual, flips,
+                                "size {} {:?} at ({}, {})",
+                                size, color, x, y
+                            );
+
+                            if flips.is_empty() {
+                                assert_eq!(played, board);
+                                continue;
+                            }
+                            expected.push((x, y));
+
+                            // 打ったマスと反転した石だけが変わる
+                            for cy in 0..size {
+                                for cx in 0..size {
+                                    let cell = if (cx, cy) == (x, y) || flips.contains(&(cx, cy)) {
+                                        Some(color)
+                                    } else {
+                                        board.get(cx, cy)
+                                    };
+                                    assert_eq!(played.get(cx, cy), cell);
+                                    assert_eq!(played.is_hole(cx, cy), board.is_hole(cx, cy));
+                                }
+                            }
+                        }
+                    }
+
+                    let mut actual = board.legal_moves(color);
+                    actual.sort_by_key(|&(x, y)| (y, x));
+                    assert_eq!(actual, expected, "size {} {:?}", size, color);
+                    assert_eq!(board.has_legal_move(color), !expected.is_empty());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn matches_reference_without_holes() {
+        check_against_reference(0.0);
+    }
+
+    #[test]
+    fn matches_reference_with_holes() {
+        check_against_reference(0.15);
+    }
+}
@@ -0,0 +1,463 @@
+//! # 定石データベース
+//!
+//! 終了した対局と読み込んだ棋譜の序盤の手から、局面ごとに打たれた手とその結果を集計する
+//!
+//! - 局面は`zobrist`のキーで索引し、回転・反転で重なる局面は1つにまとめる
+//! - 対称な局面で同じ意味になる手（初期配置の4つの初手など）も1つの手として数える
+//! - ルールの種類が異なる対局は同じ局面でも別々に集計する
+//! - 勝ち・引き分け・負けと石差は、その手を打った側から見た値を記録する
+//! - 集計するのは最後まで打たれたか投了で終わった対局だけで、サーバー上の対局は
+//!   レーティング対局に限る
+//! - ユーザーが読み込んだ棋譜は`BookSource::Imported`として別に集計する。
+//!   定石の検索結果には含めるが、コンピューターは`import_weight`の重みでしか使わない
+//!   （既定では使わない）
+//!
+//! ## 関数
+//!
+//! - `OpeningBook::add_game`:          対局の序盤の手を集計に加える
+//! - `OpeningBook::moves`:             局面の定石手と統計を取得
+//! - `OpeningBook::choose_move`:       コンピューター用に定石手を選ぶ
+//! - `OpeningBook::set_import_weight`: 読み込んだ棋譜をコンピューターが使う重みを設定
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::bitboard::Board;
+use crate::game::{DiskColor, Game, MoveAction, Termination, Variant};
+use crate::notation;
+use crate::zobrist::{self, Symmetry};
+
+/// 集計する最大の手数（パスを含む）
+pub const BOOK_MAX_PLY: usize = 20;
+/// コンピューターが定石手として選ぶのに必要な対局数
+const MIN_BOOK_GAMES: u32 = 3;
+/// 最善の得点率からこの差までの手を定石手の候補にする
+const SCORE_TOLERANCE: f64 = 0.05;
+/// コンピューターが読み込んだ棋譜の対局に掛ける既定の重み（使わない）
+const DEFAULT_IMPORT_WEIGHT: f64 = 0.0;
+
+/// 集計した対局の出どころ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookSource {
+    /// サーバー上のレーティング対局
+    Server,
+    /// ユーザーが読み込んだ棋譜（GGF・WTHOR）
+    Imported,
+}
+
+/// 局面での1つの手の集計
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct MoveStats {
+    wins: u32,
+    draws: u32,
+    losses: u32,
+    /// 打った側から見た最終的な石差の合計
+    disc_differential: i64,
+}
+
+impl MoveStats {
+    fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// 打った側の得点（勝ち1、引き分け0.5）の合計
+    fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+
+    /// 打った側の得点率
+    fn score(&self) -> f64 {
+        self.points() / self.games() as f64
+    }
+}
+
+/// 局面での1つの手の出どころごとの集計
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct SourcedStats {
+    server: MoveStats,
+    imported: MoveStats,
+}
+
+impl SourcedStats {
+    fn get_mut(&mut self, source: BookSource) -> &mut MoveStats {
+        match source {
+            BookSource::Server => &mut self.server,
+            BookSource::Imported => &mut self.imported,
+        }
+    }
+
+    /// 両方の出どころを合わせた集計
+    fn total(&self) -> MoveStats {
+        MoveStats {
+            wins: self.server.wins + self.imported.wins,
+            draws: self.server.draws + self.imported.draws,
+            losses: self.server.losses + self.imported.losses,
+            disc_differential: self.server.disc_differential + self.imported.disc_differential,
+        }
+    }
+
+    /// 読み込んだ棋譜に`import_weight`を掛けた(対局数, 得点率)
+    fn weighted(&self, import_weight: f64) -> (f64, f64) {
+        let games = self.server.games() as f64 + import_weight * self.imported.games() as f64;
+        let points = self.server.points() + import_weight * self.imported.points();
+        (games, if games > 0.0 { points / games } else { 0.0 })
+    }
+}
+
+/// 定石の手とその統計
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookMove {
+    /// 手の座標（`[x, y]`）
+    pub square: [usize; 2],
+    /// 標準座標表記（例: `f5`）
+    pub notation: String,
+    pub games: u32,
+    /// `games`のうち読み込んだ棋譜の対局数
+    pub imported_games: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// 打った側の得点率（0.0〜1.0）
+    pub score: f64,
+    /// 打った側から見た最終的な石差の平均
+    pub average_disc_differential: f64,
+}
+
+/// 局面の索引（ルールの種類と対称形で最小のキー）
+type PositionKey = (Variant, u64);
+
+/// 定石データベース
+pub struct OpeningBook {
+    /// 局面 -> (正規化した手のマス -> 集計)
+    positions: HashMap<PositionKey, HashMap<usize, SourcedStats>>,
+    /// 集計に加えたサーバー上の対局の数
+    server_games: usize,
+    /// 集計に加えた読み込んだ棋譜の対局の数
+    imported_games: usize,
+    /// コンピューターが読み込んだ棋譜の対局に掛ける重み（0で使わない、1でサーバー上の対局と同じ）
+    import_weight: f64,
+}
+
+impl Default for OpeningBook {
+    fn default() -> Self {
+        Self {
+            positions: HashMap::new(),
+            server_games: 0,
+            imported_games: 0,
+            import_weight: DEFAULT_IMPORT_WEIGHT,
+        }
+    }
+}
+
+/// 局面の対称形で最小のキーと、その最小のキーになる変換の一覧
+///
+/// 局面自体が対称な場合は複数の変換が同じキーになる
+fn orientations(board: &Board, color: DiskColor) -> (u64, Vec<Symmetry>) {
+    let keys: Vec<(u64, Symmetry)> = Symmetry::ALL
+        .into_iter()
+        .map(|symmetry| {
+            let key = zobrist::position_key(&symmetry.transform(board), color);
+            (key, symmetry)
+        })
+        .collect();
+    let min = keys.iter().map(|&(key, _)| key).min().unwrap();
+    let symmetries = keys
+        .into_iter()
+        .filter(|&(key, _)| key == min)
+        .map(|(_, symmetry)| symmetry)
+        .collect();
+    (min, symmetries)
+}
+
+/// 手のマスを正規化した局面上のマスに変換（同じ意味になるマスのうち最小の位置）
+fn canonical_square(symmetries: &[Symmetry], x: usize, y: usize, size: usize) -> usize {
+    symmetries
+        .iter()
+        .map(|symmetry| {
+            let (cx, cy) = symmetry.apply(x, y, size);
+            cy * size + cx
+        })
+        .min()
+        .unwrap()
+}
+
+impl OpeningBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 集計に加えた対局の数
+    pub fn game_count(&self, source: BookSource) -> usize {
+        match source {
+            BookSource::Server => self.server_games,
+            BookSource::Imported => self.imported_games,
+        }
+    }
+
+    /// コンピューターが定石手を選ぶときに読み込んだ棋譜の対局に掛ける重みを設定
+    pub fn set_import_weight(&mut self, weight: f64) {
+        self.import_weight = weight.max(0.0);
+    }
+
+    /// 集計した局面の数
+    pub fn position_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// 終了した対局の序盤の手を集計に加える（集計しなかった場合はfalse）
+    ///
+    /// 途中までの棋譜と、時間切れ・放棄・合意・管理者によって終わった対局は集計しない。
+    /// サーバー上の対局はカジュアル対局（コンピューターとの対局を含む）も集計しない
+    pub fn add_game(&mut self, game: &Game, source: BookSource) -> bool {
+        let Some(result) = game.result().filter(|result| {
+            matches!(
+                result.termination,
+                Termination::Normal | Termination::Resignation
+            )
+        }) else {
+            return false;
+        };
+        if source == BookSource::Server && game.config().casual {
+            return false;
+        }
+
+        let variant = game.config().variant;
+        let mut board = *game.initial_board();
+        let size = board.size();
+
+        for mv in game.move_history().iter().take(BOOK_MAX_PLY) {
+            let MoveAction::Place { x, y } = mv.action else {
+                continue;
+            };
+
+            let (key, symmetries) = orientations(&board, mv.color);
+            let square = canonical_square(&symmetries, x, y, size);
+            let (own, opponent) = result.score.points(mv.color);
+            let stats = self
+                .positions
+                .entry((variant, key))
+                .or_default()
+                .entry(square)
+                .or_default()
+                .get_mut(source);
+            match result.winner {
+                Some(winner) if winner == mv.color => stats.wins += 1,
+                Some(_) => stats.losses += 1,
+                None => stats.draws += 1,
+            }
+            stats.disc_differential += own as i64 - opponent as i64;
+
+            board.play(mv.color, x, y);
+        }
+
+        match source {
+            BookSource::Server => self.server_games += 1,
+            BookSource::Imported => self.imported_games += 1,
+        }
+        true
+    }
+
+    /// `color`の手番の局面の集計を実際の局面のマスと組にして返す
+    fn entries(
+        &self,
+        board: &Board,
+        color: DiskColor,
+        variant: Variant,
+    ) -> Vec<((usize, usize), &SourcedStats)> {
+        let (key, symmetries) = orientations(board, color);
+        let Some(entries) = self.positions.get(&(variant, key)) else {
+            return Vec::new();
+        };
+
+        // 正規化した局面上のマスを実際の局面のマスに戻す
+        let size = board.size();
+        let inverse = symmetries[0].inverse();
+        entries
+            .iter()
+            .map(|(&square, stats)| (inverse.apply(square % size, square / size, size), stats))
+            .collect()
+    }
+
+    /// `color`の手番の局面で打たれた定石手（対局数の多い順）
+    pub fn moves(&self, board: &Board, color: DiskColor, variant: Variant) -> Vec<BookMove> {
+        let mut moves: Vec<BookMove> = self
+            .entries(board, color, variant)
+            .into_iter()
+            .map(|((x, y), sourced)| {
+                let stats = sourced.total();
+                let games = stats.games();
+                BookMove {
+                    square: [x, y],
+                    notation: notation::format_square(x, y),
+                    games,
+                    imported_games: sourced.imported.games(),
+                    wins: stats.wins,
+                    draws: stats.draws,
+                    losses: stats.losses,
+                    score: stats.score(),
+                    average_disc_differential: stats.disc_differential as f64 / games as f64,
+                }
+            })
+            .collect();
+
+        moves.sort_by(|a, b| {
+            b.games
+                .cmp(&a.games)
+                .then(b.score.total_cmp(&a.score))
+                .then(a.square.cmp(&b.square))
+        });
+        moves
+    }
+
+    /// コンピューターが打つ定石手（十分な対局数の手がない場合はNone）
+    ///
+    /// 読み込んだ棋譜の対局は`import_weight`を掛けて数え、
+    /// 得点率が最善に近い手から、対局数に比例した確率で選ぶ
+    pub fn choose_move<R: Rng>(
+        &self,
+        board: &Board,
+        color: DiskColor,
+        variant: Variant,
+        rng: &mut R,
+    ) -> Option<(usize, usize)> {
+        let legal_moves = board.legal_moves(color);
+        // (マス, 対局数, 得点率)
+        let mut candidates: Vec<((usize, usize), f64, f64)> = self
+            .entries(board, color, variant)
+            .into_iter()
+            .filter(|(square, _)| legal_moves.contains(square))
+            .map(|(square, stats)| {
+                let (games, score) = stats.weighted(self.import_weight);
+                (square, games, score)
+            })
+            .filter(|&(_, games, _)| games >= MIN_BOOK_GAMES as f64)
+            .collect();
+        // 対局数の重みで選ぶ際に結果が集計の順序によらないよう並べておく
+        candidates.sort_by_key(|&(square, _, _)| square);
+        let best = candidates
+            .iter()
+            .map(|&(_, _, score)| score)
+            .max_by(|a, b| a.total_cmp(b))?;
+
+        let candidates: Vec<_> = candidates
+            .into_iter()
+            .filter(|&(_, _, score)| score >= best - SCORE_TOLERANCE)
+            .collect();
+        let &(square, _, _) = candidates
+            .choose_weighted(rng, |&(_, games, _)| games)
+            .ok()?;
+        Some(square)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameConfig;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    /// 黒がf5を打ってから`termination`で終わった対局
+    fn game(casual: bool, termination: Termination) -> Game {
+        let config = GameConfig {
+            casual,
+            ..GameConfig::default()
+        };
+        let mut game = Game::new(
+            "book".to_string(),
+            "b".to_string(),
+            "Black".to_string(),
+            "w".to_string(),
+            "White".to_string(),
+            config,
+            None,
+        );
+        game.make_move("b", 5, 4).unwrap();
+        match termination {
+            Termination::Resignation => game.resign("w").map(|_| ()),
+            Termination::Abandonment => game.abandon("w").map(|_| ()),
+            _ => game.conclude(termination, None).map(|_| ()),
+        }
+        .unwrap();
+        game
+    }
+
+    #[test]
+    fn counts_only_completed_rated_server_games() {
+        let mut book = OpeningBook::default();
+
+        assert!(book.add_game(&game(false, Termination::Resignation), BookSource::Server));
+        assert!(!book.add_game(&game(false, Termination::Abandonment), BookSource::Server));
+        assert!(!book.add_game(&game(false, Termination::Timeout), BookSource::Server));
+        assert!(!book.add_game(&game(false, Termination::Admin), BookSource::Server));
+        assert!(!book.add_game(&game(true, Termination::Resignation), BookSource::Server));
+        assert_eq!(book.game_count(BookSource::Server), 1);
+
+        // 途中までの棋譜は集計しない
+        let config = GameConfig::default();
+        let mut in_progress = Game::new(
+            "book".to_string(),
+            "b".to_string(),
+            "Black".to_string(),
+            "w".to_string(),
+            "White".to_string(),
+            config,
+            None,
+        );
+        in_progress.make_move("b", 5, 4).unwrap();
+        assert!(!book.add_game(&in_progress, BookSource::Server));
+    }
+
+    #[test]
+    fn imported_games_are_separate_and_unweighted_by_default() {
+        let mut book = OpeningBook::default();
+        for _ in 0..MIN_BOOK_GAMES {
+            assert!(book.add_game(&game(false, Termination::Resignation), BookSource::Imported));
+        }
+        assert_eq!(book.game_count(BookSource::Server), 0);
+        assert_eq!(
+            book.game_count(BookSource::Imported),
+            MIN_BOOK_GAMES as usize
+        );
+
+        let board = Board::initial(8);
+        let moves = book.moves(&board, DiskColor::Black, Variant::Standard);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].games, MIN_BOOK_GAMES);
+        assert_eq!(moves[0].imported_games, MIN_BOOK_GAMES);
+        assert_eq!(moves[0].wins, MIN_BOOK_GAMES);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            book.choose_move(&board, DiskColor::Black, Variant::Standard, &mut rng),
+            None
+        );
+
+        // 初期配置は対称なので、f5と同じ手として実際の局面のマスに戻した手を選ぶ
+        book.set_import_weight(1.0);
+        let [x, y] = moves[0].square;
+        assert_eq!(
+            book.choose_move(&board, DiskColor::Black, Variant::Standard, &mut rng),
+            Some((x, y))
+        );
+    }
+
+    #[test]
+    fn server_games_are_used_by_the_computer() {
+        let mut book = OpeningBook::default();
+        for _ in 0..MIN_BOOK_GAMES {
+            book.add_game(&game(false, Termination::Resignation), BookSource::Server);
+        }
+
+        let board = Board::initial(8);
+        let [x, y] = book.moves(&board, DiskColor::Black, Variant::Standard)[0].square;
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            book.choose_move(&board, DiskColor::Black, Variant::Standard, &mut rng),
+            Some((x, y))
+        );
+    }
+}
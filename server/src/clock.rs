@@ -0,0 +1,364 @@
+//! # 対局時計
+//!
+//! 持ち時間の方式ごとに両プレイヤーの残り時間を管理する
+//!
+//! - 切れ負け: 持ち時間を使い切ると負け
+//! - フィッシャー: 1手ごとに加算時間が持ち時間に加わる
+//! - 秒読み: 持ち時間を使い切った後は1手ごとに秒読みの時間内に打つ。
+//!   秒読みを超えるたびに回数を1つ消費し、回数がなくなると負け
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::game::DiskColor;
+
+/// 持ち時間の方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimeControl {
+    /// 時間制限なし
+    #[default]
+    Unlimited,
+    /// 切れ負け
+    SuddenDeath {
+        /// 持ち時間（秒）
+        base_secs: u64,
+    },
+    /// フィッシャー方式
+    Fischer {
+        /// 持ち時間（秒）
+        base_secs: u64,
+        /// 1手ごとの加算時間（秒）
+        increment_secs: u64,
+    },
+    /// 秒読み
+    ByoYomi {
+        /// 持ち時間（秒）
+        base_secs: u64,
+        /// 秒読みの時間（秒）
+        period_secs: u64,
+        /// 秒読みの回数
+        periods: u32,
+    },
+}
+
+impl TimeControl {
+    /// 設定が有効か確認
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            TimeControl::Unlimited => Ok(()),
+            TimeControl::SuddenDeath { base_secs } | TimeControl::Fischer { base_secs, .. } => {
+                if base_secs == 0 {
+                    return Err("Time control needs a positive base time".to_string());
+                }
+                Ok(())
+            }
+            TimeControl::ByoYomi {
+                period_secs,
+                periods,
+                ..
+            } => {
+                if period_secs == 0 || periods == 0 {
+                    return Err("Byo-yomi needs a positive period length and count".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// 片方のプレイヤーの残り時間
+#[derive(Debug, Clone, Copy)]
+struct PlayerTime {
+    /// 残りの持ち時間
+    main: Duration,
+    /// 残りの秒読みの回数
+    periods: u32,
+}
+
+/// 対局時計
+#[derive(Debug, Clone)]
+pub struct Clock {
+    time_control: TimeControl,
+    black: PlayerTime,
+    white: PlayerTime,
+    /// 時計が動いている側と動き始めた時刻
+    running: Option<(DiskColor, Instant)>,
+}
+
+/// クライアントに送る時計の状態
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockState {
+    /// 黒の残り時間（ミリ秒、秒読み中は現在の秒読みの残り）
+    pub black_ms: u64,
+    /// 白の残り時間（ミリ秒、秒読み中は現在の秒読みの残り）
+    pub white_ms: u64,
+    /// 黒の残りの秒読みの回数
+    pub black_periods: u32,
+    /// 白の残りの秒読みの回数
+    pub white_periods: u32,
+    /// 時計が動いている側
+    pub running: Option<DiskColor>,
+}
+
+impl Clock {
+    /// 持ち時間の方式から時計を作成（時間制限なしの場合はNone）
+    pub fn new(time_control: TimeControl) -> Option<Self> {
+        let initial = match time_control {
+            TimeControl::Unlimited => return None,
+            TimeControl::SuddenDeath { base_secs } | TimeControl::Fischer { base_secs, .. } => {
+                PlayerTime {
+                    main: Duration::from_secs(base_secs),
+                    periods: 0,
+                }
+            }
+            TimeControl::ByoYomi {
+                base_secs, periods, ..
+            } => PlayerTime {
+                main: Duration::from_secs(base_secs),
+                periods,
+            },
+        };
+
+        Some(Self {
+            time_control,
+            black: initial,
+            white: initial,
+            running: None,
+        })
+    }
+
+    fn player(&self, color: DiskColor) -> &PlayerTime {
+        match color {
+            DiskColor::Black => &self.black,
+            DiskColor::White => &self.white,
+        }
+    }
+
+    fn player_mut(&mut self, color: DiskColor) -> &mut PlayerTime {
+        match color {
+            DiskColor::Black => &mut self.black,
+            DiskColor::White => &mut self.white,
+        }
+    }
+
+    /// 秒読み1回分の時間
+    fn period(&self) -> Duration {
+        match self.time_control {
+            TimeControl::ByoYomi { period_secs, .. } => Duration::from_secs(period_secs),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// 指定した側の時計を動かす（動いていた時計は経過時間を差し引いて止める）
+    pub fn start(&mut self, color: DiskColor, now: Instant) {
+        self.stop(now);
+        self.running = Some((color, now));
+    }
+
+    /// 動いている時計を止めて経過時間を差し引く
+    pub fn stop(&mut self, now: Instant) {
+        let Some((color, since)) = self.running.take() else {
+            return;
+        };
+        let elapsed = now.saturating_duration_since(since);
+        let period = self.period();
+        let player = self.player_mut(color);
+
+        if elapsed <= player.main {
+            player.main -= elapsed;
+        } else if !period.is_zero() {
+            // 秒読みを超えた回数だけ消費する
+            let over = elapsed - player.main;
+            let used = (over.as_millis() / period.as_millis()) as u32;
+            player.main = Duration::ZERO;
+            player.periods = player.periods.saturating_sub(used);
+        } else {
+            player.main = Duration::ZERO;
+        }
+    }
+
+    /// 手を打った側の時計を止め、相手の時計を動かす（フィッシャー方式では加算時間を加える）
+    pub fn press(&mut self, mover: DiskColor, next: DiskColor, now: Instant) {
+        self.stop(now);
+        if let TimeControl::Fischer { increment_secs, .. } = self.time_control {
+            self.player_mut(mover).main += Duration::from_secs(increment_secs);
+        }
+        self.running = Some((next, now));
+    }
+
+    /// 指定した側が時間切れになるまでの残り時間
+    pub fn time_left(&self, color: DiskColor, now: Instant) -> Duration {
+        let player = self.player(color);
+        let total = player.main + self.period() * player.periods;
+        match self.running {
+            Some((running, since)) if running == color => {
+                total.saturating_sub(now.saturating_duration_since(since))
+            }
+            _ => total,
+        }
+    }
+
+    /// 時間切れになった側（動いている側のみ判定する）
+    pub fn flagged(&self, now: Instant) -> Option<DiskColor> {
+        let (color, _) = self.running?;
+        self.time_left(color, now).is_zero().then_some(color)
+    }
+
+    /// クライアントに送る時計の状態
+    pub fn state(&self, now: Instant) -> ClockState {
+        let display = |color: DiskColor| {
+            let player = self.player(color);
+            let left = self.time_left(color, now);
+            let periods_time = self.period() * player.periods;
+
+            if left > periods_time || periods_time.is_zero() {
+                // 持ち時間の残り
+                (left - periods_time, player.periods)
+            } else if left.is_zero() {
+                (Duration::ZERO, 0)
+            } else {
+                // 秒読み中は現在の秒読みの残りと、それを含む残り回数
+                let periods = left.as_millis().div_ceil(self.period().as_millis()) as u32;
+                (left - self.period() * (periods - 1), periods)
+            }
+        };
+
+        let (black, black_periods) = display(DiskColor::Black);
+        let (white, white_periods) = display(DiskColor::White);
+        ClockState {
+            black_ms: black.as_millis() as u64,
+            white_ms: white.as_millis() as u64,
+            black_periods,
+            white_periods,
+            running: self.running.map(|(color, _)| color),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{Game, GameConfig, Termination};
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    /// 黒の時計を動かし始めた時計と開始時刻
+    fn started(time_control: TimeControl) -> (Clock, Instant) {
+        let mut clock = Clock::new(time_control).unwrap();
+        let start = Instant::now();
+        clock.start(DiskColor::Black, start);
+        (clock, start)
+    }
+
+    #[test]
+    fn unlimited_has_no_clock() {
+        assert!(Clock::new(TimeControl::Unlimited).is_none());
+    }
+
+    #[test]
+    fn sudden_death_flag_fall() {
+        let (mut clock, start) = started(TimeControl::SuddenDeath { base_secs: 60 });
+        assert_eq!(clock.flagged(start + 59 * SECOND), None);
+        assert_eq!(clock.flagged(start + 60 * SECOND), Some(DiskColor::Black));
+
+        // 黒が30秒で打つと白の時計が動き、黒の残りは30秒
+        clock.press(DiskColor::Black, DiskColor::White, start + 30 * SECOND);
+        assert_eq!(
+            clock.time_left(DiskColor::Black, start + 100 * SECOND),
+            30 * SECOND
+        );
+        assert_eq!(clock.flagged(start + 89 * SECOND), None);
+        assert_eq!(clock.flagged(start + 90 * SECOND), Some(DiskColor::White));
+    }
+
+    #[test]
+    fn fischer_adds_increment() {
+        let (mut clock, start) = started(TimeControl::Fischer {
+            base_secs: 10,
+            increment_secs: 5,
+        });
+
+        // 8秒使って5秒加算されるので残りは7秒
+        clock.press(DiskColor::Black, DiskColor::White, start + 8 * SECOND);
+        assert_eq!(
+            clock.time_left(DiskColor::Black, start + 8 * SECOND),
+            7 * SECOND
+        );
+
+        let now = start + 9 * SECOND;
+        clock.press(DiskColor::White, DiskColor::Black, now);
+        assert_eq!(clock.time_left(DiskColor::White, now), 14 * SECOND);
+        assert_eq!(clock.flagged(now + 6 * SECOND), None);
+        assert_eq!(clock.flagged(now + 7 * SECOND), Some(DiskColor::Black));
+    }
+
+    #[test]
+    fn byo_yomi_consumes_periods() {
+        let (mut clock, start) = started(TimeControl::ByoYomi {
+            base_secs: 10,
+            period_secs: 5,
+            periods: 2,
+        });
+
+        // 持ち時間を2秒超えても秒読みの範囲内なら回数は減らない
+        let now = start + 12 * SECOND;
+        clock.press(DiskColor::Black, DiskColor::White, now);
+        let state = clock.state(now);
+        assert_eq!((state.black_ms, state.black_periods), (5_000, 2));
+
+        // 秒読みを1回超えると回数を1つ消費する
+        let now = now + SECOND;
+        clock.press(DiskColor::White, DiskColor::Black, now);
+        let now = now + 7 * SECOND;
+        clock.press(DiskColor::Black, DiskColor::White, now);
+        assert_eq!(clock.time_left(DiskColor::Black, now), 5 * SECOND);
+
+        let now = now + SECOND;
+        clock.press(DiskColor::White, DiskColor::Black, now);
+        assert_eq!(clock.flagged(now + 4 * SECOND), None);
+        assert_eq!(clock.flagged(now + 5 * SECOND), Some(DiskColor::Black));
+        let state = clock.state(now + 5 * SECOND);
+        assert_eq!((state.black_ms, state.black_periods), (0, 0));
+    }
+
+    #[test]
+    fn game_ends_on_timeout() {
+        let time_controls = [
+            TimeControl::SuddenDeath { base_secs: 60 },
+            TimeControl::Fischer {
+                base_secs: 60,
+                increment_secs: 2,
+            },
+            TimeControl::ByoYomi {
+                base_secs: 50,
+                period_secs: 5,
+                periods: 2,
+            },
+        ];
+
+        for time_control in time_controls {
+            let mut game = Game::new(
+                "clock".to_string(),
+                "black-id".to_string(),
+                "black".to_string(),
+                "white-id".to_string(),
+                "white".to_string(),
+                GameConfig {
+                    time_control,
+                    ..GameConfig::default()
+                },
+                None,
+            );
+            let now = Instant::now();
+            assert!(game.check_timeout(now).is_none());
+
+            let state = game.check_timeout(now + 61 * SECOND).unwrap();
+            assert!(state.is_game_over);
+            let result = game.result().unwrap();
+            assert_eq!(result.termination, Termination::Timeout);
+            assert_eq!(result.winner, Some(DiskColor::White));
+            assert!(result.score.white > result.score.black);
+        }
+    }
+}
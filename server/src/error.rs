@@ -0,0 +1,97 @@
+//! # ゲームのエラー
+//!
+//! 手や投了が受け付けられなかった理由
+//!
+//! クライアントが文字列を照合せずに対応できるよう、エラーごとに変わらないコードを持ち、
+//! `ServerMessage::Error`の`code`として送信する
+
+use std::fmt;
+
+/// 手や投了が受け付けられなかった理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    /// 相手の手番
+    NotYourTurn,
+    /// 既に石が置かれているマス
+    SquareOccupied,
+    /// 穴のマス
+    SquareBlocked,
+    /// 相手の石を1つも挟めないマス
+    NoFlips,
+    /// 盤面の外の座標
+    OutOfBounds,
+    /// 表記を解析できない
+    InvalidNotation(String),
+    /// 打てるマスがあるのにパスしようとした
+    IllegalPass,
+    /// ゲームが既に終了している
+    GameOver,
+    /// 持ち時間が切れている
+    TimeUp,
+    /// ゲームの対局者ではない
+    NotParticipant,
+    /// 対局中のゲームがない
+    NotInGame,
+    /// ゲームが見つからない
+    GameNotFound,
+    /// レーティングに反映する対局ではヒントを使えない
+    HintsNotAllowed,
+    /// ヒントを使える回数を使い切った
+    HintLimitReached,
+    /// 前に要求した局面の評価が終わっていない
+    HintPending,
+}
+
+impl GameError {
+    /// 変わらない機械可読なエラーコード
+    pub fn code(&self) -> &'static str {
+        match self {
+            GameError::NotYourTurn => "not_your_turn",
+            GameError::SquareOccupied => "square_occupied",
+            GameError::SquareBlocked => "square_blocked",
+            GameError::NoFlips => "no_flips",
+            GameError::OutOfBounds => "out_of_bounds",
+            GameError::InvalidNotation(_) => "invalid_notation",
+            GameError::IllegalPass => "illegal_pass",
+            GameError::GameOver => "game_over",
+            GameError::TimeUp => "time_up",
+            GameError::NotParticipant => "not_participant",
+            GameError::NotInGame => "not_in_game",
+            GameError::GameNotFound => "game_not_found",
+            GameError::HintsNotAllowed => "hints_not_allowed",
+            GameError::HintLimitReached => "hint_limit_reached",
+            GameError::HintPending => "hint_pending",
+        }
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::NotYourTurn => write!(f, "It's not your turn"),
+            GameError::SquareOccupied => write!(f, "Square is already occupied"),
+            GameError::SquareBlocked => write!(f, "Square is blocked"),
+            GameError::NoFlips => write!(f, "Invalid move: no discs to flip"),
+            GameError::OutOfBounds => write!(f, "Invalid coordinates"),
+            GameError::InvalidNotation(message) => write!(f, "{}", message),
+            GameError::IllegalPass => write!(f, "Cannot pass while you have a legal move"),
+            GameError::GameOver => write!(f, "Game is already over"),
+            GameError::TimeUp => write!(f, "Time is up"),
+            GameError::NotParticipant => write!(f, "You are not a player in this game"),
+            GameError::NotInGame => write!(f, "You are not in a game"),
+            GameError::GameNotFound => write!(f, "Game not found"),
+            GameError::HintsNotAllowed => write!(f, "Hints are only available in casual games"),
+            GameError::HintLimitReached => write!(f, "No hints left in this game"),
+            GameError::HintPending => write!(f, "Previous hint is still being evaluated"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// 棋譜の読み込みなど`String`のエラーを返す処理で`?`を使えるようにする
+impl From<GameError> for String {
+    fn from(error: GameError) -> Self {
+        error.to_string()
+    }
+}
@@ -1,23 +1,35 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::time::Instant;
 
-use crate::{app_log, debug_log, info_log};
+use crate::bitboard::{Board, DEFAULT_BOARD_SIZE, SUPPORTED_BOARD_SIZES};
+use crate::clock::{Clock, ClockState, TimeControl};
+use crate::error::GameError;
+use crate::mask::{self, BoardMask};
+use crate::notation;
+use crate::opening::{self, StartPosition};
+use crate::zobrist;
+use crate::{app_log, debug_log, info_log, warning_log};
 
-const BOARD_SIZE: usize = 8;
-
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DiskColor {
     Black,
     White,
 }
 
-impl DiskColor {
-    pub fn to_string(&self) -> String {
+impl fmt::Display for DiskColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            DiskColor::Black => "black".to_string(),
-            DiskColor::White => "white".to_string(),
+            DiskColor::Black => write!(f, "black"),
+            DiskColor::White => write!(f, "white"),
         }
     }
+}
 
+impl DiskColor {
     pub fn opposite(&self) -> DiskColor {
         match self {
             DiskColor::Black => DiskColor::White,
@@ -26,30 +38,396 @@ impl DiskColor {
     }
 }
 
+/// ルールの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /// 通常のオセロ（石が多い方が勝ち）
+    #[default]
+    Standard,
+    /// アンチリバーシ（石が少ない方が勝ち）
+    AntiReversi,
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Variant::Standard => write!(f, "standard"),
+            Variant::AntiReversi => write!(f, "anti_reversi"),
+        }
+    }
+}
+
+impl Variant {
+    /// 終局時の石数から勝者の色を決定（引き分けはNone）
+    pub fn winner(&self, black_count: usize, white_count: usize) -> Option<DiskColor> {
+        let leader = match black_count.cmp(&white_count) {
+            Ordering::Greater => DiskColor::Black,
+            Ordering::Less => DiskColor::White,
+            Ordering::Equal => return None,
+        };
+
+        match self {
+            Variant::Standard => Some(leader),
+            Variant::AntiReversi => Some(leader.opposite()),
+        }
+    }
+}
+
+/// ゲームの設定
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GameConfig {
+    /// 盤面の一辺の長さ
+    pub board_size: usize,
+    /// ルールの種類
+    #[serde(default)]
+    pub variant: Variant,
+    /// 開始局面の種類
+    #[serde(default)]
+    pub start: StartPosition,
+    /// 穴を開けるマスのテンプレート
+    #[serde(default)]
+    pub mask: BoardMask,
+    /// 持ち時間の方式
+    #[serde(default)]
+    pub time_control: TimeControl,
+    /// レーティングに反映しない対局か（ヒントを使える）
+    #[serde(default)]
+    pub casual: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            board_size: DEFAULT_BOARD_SIZE,
+            variant: Variant::Standard,
+            start: StartPosition::Standard,
+            mask: BoardMask::Full,
+            time_control: TimeControl::Unlimited,
+            casual: false,
+        }
+    }
+}
+
+impl GameConfig {
+    /// 設定が有効か確認
+    pub fn validate(&self) -> Result<(), String> {
+        if !SUPPORTED_BOARD_SIZES.contains(&self.board_size) {
+            return Err(format!(
+                "Unsupported board size: {}. Supported sizes: {:?}",
+                self.board_size, SUPPORTED_BOARD_SIZES
+            ));
+        }
+        if self.start == StartPosition::Xot && self.board_size != DEFAULT_BOARD_SIZE {
+            return Err(format!(
+                "XOT openings are only available on {}x{} boards",
+                DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE
+            ));
+        }
+        if self.start == StartPosition::Xot && self.mask != BoardMask::Full {
+            return Err("XOT openings cannot be combined with blocked squares".to_string());
+        }
+        self.time_control.validate()
+    }
+}
+
+/// 終局時のスコア（WOFルールに基づく両者の得点）
+///
+/// 得点の合計は常に石を置けるマスの数（8x8なら64）になる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FinalScore {
+    pub black: usize,
+    pub white: usize,
+}
+
+impl FinalScore {
+    /// 盤面から勝者（引き分けはNone）の得点を計算
+    ///
+    /// 空きマスは勝者に与え、引き分けの場合は等分する。
+    /// アンチリバーシでは相手の石の数を自分の得点として数える
+    fn count(board: &Board, variant: Variant, winner: Option<DiskColor>) -> Self {
+        let (black, white) = match variant {
+            Variant::Standard => (board.count(DiskColor::Black), board.count(DiskColor::White)),
+            Variant::AntiReversi => (board.count(DiskColor::White), board.count(DiskColor::Black)),
+        };
+        let empties = board.empty_count();
+
+        match winner {
+            Some(DiskColor::Black) => FinalScore {
+                black: black + empties,
+                white,
+            },
+            Some(DiskColor::White) => FinalScore {
+                black,
+                white: white + empties,
+            },
+            None => FinalScore {
+                black: black + empties / 2,
+                white: white + empties - empties / 2,
+            },
+        }
+    }
+
+    /// 両者とも打てなくなった通常の終局
+    pub fn completed(board: &Board, variant: Variant) -> Self {
+        let winner = variant.winner(board.count(DiskColor::Black), board.count(DiskColor::White));
+        Self::count(board, variant, winner)
+    }
+
+    /// 投了（勝者が全てのマスを得る）
+    pub fn resignation(board: &Board, winner: DiskColor) -> Self {
+        let total =
+            board.count(DiskColor::Black) + board.count(DiskColor::White) + board.empty_count();
+        Self::with_winner(winner, total, 0)
+    }
+
+    /// 時間切れ（空きマスを勝者に与え、それでも勝者が上回らなければ最小差の勝ち）
+    pub fn timeout(board: &Board, variant: Variant, winner: DiskColor) -> Self {
+        let score = Self::count(board, variant, Some(winner));
+        let (winner_points, loser_points) = score.points(winner);
+        if winner_points > loser_points {
+            return score;
+        }
+
+        // 64マスなら33-31
+        let total = winner_points + loser_points;
+        Self::with_winner(winner, total / 2 + 1, total - (total / 2 + 1))
+    }
+
+    fn with_winner(winner: DiskColor, winner_points: usize, loser_points: usize) -> Self {
+        match winner {
+            DiskColor::Black => FinalScore {
+                black: winner_points,
+                white: loser_points,
+            },
+            DiskColor::White => FinalScore {
+                black: loser_points,
+                white: winner_points,
+            },
+        }
+    }
+
+    /// 合意の引き分けなど、石の数によらず全てのマスを等分する
+    pub fn draw(board: &Board) -> Self {
+        let total =
+            board.count(DiskColor::Black) + board.count(DiskColor::White) + board.empty_count();
+        FinalScore {
+            black: total / 2,
+            white: total - total / 2,
+        }
+    }
+
+    /// 指定した側とその相手の得点
+    pub fn points(&self, color: DiskColor) -> (usize, usize) {
+        match color {
+            DiskColor::Black => (self.black, self.white),
+            DiskColor::White => (self.white, self.black),
+        }
+    }
+
+    /// 黒から見た得点差
+    pub fn margin(&self) -> i32 {
+        self.black as i32 - self.white as i32
+    }
+}
+
+impl fmt::Display for FinalScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.black, self.white)
+    }
+}
+
+/// 終局の理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Termination {
+    /// 両者とも打てなくなった通常の終局
+    Normal,
+    /// 投了
+    Resignation,
+    /// 時間切れ
+    Timeout,
+    /// 対局中の切断による放棄
+    Abandonment,
+    /// 合意の引き分け
+    AgreedDraw,
+    /// 管理者による終了
+    Admin,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Termination::Normal => write!(f, "Game completed"),
+            Termination::Resignation => write!(f, "Resignation"),
+            Termination::Timeout => write!(f, "Time forfeit"),
+            Termination::Abandonment => write!(f, "Player disconnected"),
+            Termination::AgreedDraw => write!(f, "Draw agreed"),
+            Termination::Admin => write!(f, "Terminated by administrator"),
+        }
+    }
+}
+
+/// 対局の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GameResult {
+    /// 終局の理由
+    pub termination: Termination,
+    /// 勝者の色（引き分けはNone）
+    pub winner: Option<DiskColor>,
+    /// 最終スコア
+    pub score: FinalScore,
+}
+
+impl GameResult {
+    /// 指定した側から見た結果（勝ち1.0、引き分け0.5、負け0.0）
+    pub fn outcome(&self, color: DiskColor) -> f64 {
+        match self.winner {
+            Some(winner) if winner == color => 1.0,
+            Some(_) => 0.0,
+            None => 0.5,
+        }
+    }
+
+    /// レーティングに反映する結果か（管理者による終了は反映しない）
+    pub fn is_rated(&self) -> bool {
+        self.termination != Termination::Admin
+    }
+}
+
+/// 1局で1人が使えるヒントの回数
+pub const MAX_HINTS: usize = 3;
+
+/// 対局中に使ったヒント
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HintRecord {
+    /// ヒントを使った時点の手数（パスを含む）
+    pub ply: usize,
+    /// ヒントを使った側の色
+    pub color: DiskColor,
+}
+
+/// 置き石の最大数
+pub const MAX_HANDICAP_DISCS: usize = 4;
+/// 置き石1つに相当するレーティング差
+pub const RATING_PER_HANDICAP_DISC: u32 = 200;
+
+/// 隅に石を置いて始めるハンディキャップ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Handicap {
+    /// 置き石を受け取る側の色
+    pub color: DiskColor,
+    /// 置き石の数（1〜4）
+    pub discs: usize,
+}
+
+impl Handicap {
+    /// レーティング差から置き石を決める（差が小さい場合はNone）
+    ///
+    /// `RATING_PER_HANDICAP_DISC`ごとに1つ、最大`MAX_HANDICAP_DISCS`個を弱い側に与える
+    pub fn from_ratings(black_rating: u32, white_rating: u32) -> Option<Handicap> {
+        let gap = black_rating.abs_diff(white_rating);
+        let discs = ((gap / RATING_PER_HANDICAP_DISC) as usize).min(MAX_HANDICAP_DISCS);
+        if discs == 0 {
+            return None;
+        }
+
+        let color = if black_rating < white_rating {
+            DiskColor::Black
+        } else {
+            DiskColor::White
+        };
+        Some(Handicap { color, discs })
+    }
+
+    /// 置き石に相当するレーティング差（置き石を受け取る側に有利な分）
+    ///
+    /// レーティング更新時の期待勝率の補正に使う
+    pub fn rating_equivalent(&self) -> u32 {
+        self.discs as u32 * RATING_PER_HANDICAP_DISC
+    }
+
+    /// 置き石を置く隅（a1、h8、h1、a8の順）
+    pub fn squares(&self, board_size: usize) -> Vec<(usize, usize)> {
+        let last = board_size - 1;
+        [(0, 0), (last, last), (last, 0), (0, last)]
+            .into_iter()
+            .take(self.discs)
+            .collect()
+    }
+
+    /// 設定と組み合わせられるか確認
+    ///
+    /// 置き石は通常の初期配置で、隅が穴にならない盤面でのみ使える
+    pub fn validate(&self, config: &GameConfig) -> Result<(), String> {
+        if !(1..=MAX_HANDICAP_DISCS).contains(&self.discs) {
+            return Err(format!(
+                "Invalid handicap: {} discs (expected 1 to {})",
+                self.discs, MAX_HANDICAP_DISCS
+            ));
+        }
+        if config.start != StartPosition::Standard {
+            return Err("Handicap requires the standard start position".to_string());
+        }
+        if config.mask != BoardMask::Full {
+            return Err("Handicap cannot be combined with blocked squares".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
 pub struct Game {
     pub game_id: String,
     pub black_player_id: String,
     pub black_player_name: String,
     pub white_player_id: String,
     pub white_player_name: String,
-    board: Vec<Vec<Option<DiskColor>>>,
+    /// 対局開始日時
+    pub created_at: DateTime<Utc>,
+    /// 開始局面までの手順（通常の初期配置から始めた場合はNone）
+    pub opening: Option<String>,
+    config: GameConfig,
+    /// 置き石（ハンディキャップなしの場合はNone）
+    handicap: Option<Handicap>,
+    /// 対局時計（時間制限なしの場合はNone）
+    clock: Option<Clock>,
+    initial_board: Board,
+    initial_color: DiskColor,
+    board: Board,
+    /// 現在の盤面のZobristキー（手番を含まない）
+    board_hash: u64,
     current_color: DiskColor,
-    is_game_over: bool,
-    winner: Option<String>,
+    /// 対局の結果（未終了の場合はNone）
+    result: Option<GameResult>,
     move_history: Vec<GameMove>,
+    /// 使ったヒントの履歴
+    hints: Vec<HintRecord>,
+}
+
+/// 手の内容
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveAction {
+    /// 石を置く
+    Place { x: usize, y: usize },
+    /// 打てる場所がなくパス
+    Pass,
 }
 
 #[derive(Clone, Debug)]
 pub struct GameMove {
     pub player_id: String,
-    pub x: usize,
-    pub y: usize,
+    pub action: MoveAction,
     pub color: DiskColor,
+    /// この手で反転した石の座標一覧（パスの場合は空）
+    pub flipped: Vec<(usize, usize)>,
 }
 
 #[derive(Clone, Debug)]
 pub struct GameState {
     pub board: Vec<Vec<Option<String>>>,
+    pub board_size: usize,
+    pub variant: Variant,
     pub current_player: String,
     pub is_game_over: bool,
     pub winner: Option<String>,
@@ -57,34 +435,95 @@ pub struct GameState {
     pub white_count: usize,
     pub black_can_move: bool,
     pub white_can_move: bool,
+    /// 手番側が打てるマスの一覧（終局後は空）
+    pub legal_moves: Vec<(usize, usize)>,
+    /// 石を置けない穴のマスの一覧
+    pub holes: Vec<(usize, usize)>,
+    /// 最後に石が置かれたマス
+    pub last_move: Option<(usize, usize)>,
+    /// 最後の手で反転した石の座標一覧
+    pub flipped: Vec<(usize, usize)>,
+    /// 局面のZobristキー
+    pub position_key: u64,
+    /// 両プレイヤーの残り時間（時間制限なしの場合はNone）
+    pub clock: Option<ClockState>,
 }
 
-const DIRECTIONS: [(i32, i32); 8] = [
-    (-1, -1),
-    (-1, 0),
-    (-1, 1),
-    (0, -1),
-    (0, 1),
-    (1, -1),
-    (1, 0),
-    (1, 1),
-];
-
 impl Game {
+    /// 設定の盤面マスクと開始局面の種類に従ってゲームを作成
+    ///
+    /// XOTとランダム開始の場合は選ばれた手順を`opening`に記録する。
+    /// `handicap`を指定すると置き石を受け取る側の隅に石を置いてから始める
+    /// （設定と組み合わせられない置き石は無視する）
     pub fn new(
         game_id: String,
         black_player_id: String,
         black_player_name: String,
         white_player_id: String,
         white_player_name: String,
+        config: GameConfig,
+        handicap: Option<Handicap>,
     ) -> Self {
-        let mut board = vec![vec![None; BOARD_SIZE]; BOARD_SIZE];
+        let mut rng = rand::thread_rng();
+        let mut base = Board::initial(config.board_size);
+        mask::apply(&mut base, config.mask, &mut rng);
 
-        // 初期配置
-        board[3][3] = Some(DiskColor::White);
-        board[3][4] = Some(DiskColor::Black);
-        board[4][3] = Some(DiskColor::Black);
-        board[4][4] = Some(DiskColor::White);
+        let handicap = handicap.filter(|handicap| match handicap.validate(&config) {
+            Ok(()) => true,
+            Err(e) => {
+                warning_log!("Handicap ignored for game {}: {}", game_id, e);
+                false
+            }
+        });
+        if let Some(handicap) = handicap {
+            for (x, y) in handicap.squares(config.board_size) {
+                base.set(x, y, Some(handicap.color));
+            }
+        }
+
+        let (board, side_to_move, transcript) = match opening::choose(config.start, &base, &mut rng)
+        {
+            Some(opening) => (
+                opening.board,
+                opening.side_to_move,
+                Some(opening.transcript),
+            ),
+            None => (base, DiskColor::Black, None),
+        };
+
+        let mut game = Self::with_board(
+            game_id,
+            black_player_id,
+            black_player_name,
+            white_player_id,
+            white_player_name,
+            config,
+            board,
+            side_to_move,
+        );
+        game.opening = transcript;
+        game.handicap = handicap;
+        game
+    }
+
+    /// 盤面と手番を指定してゲームを作成（パスと終局の判定は行わない）
+    ///
+    /// 持ち時間がある場合は手番側の時計を動かし始める
+    #[allow(clippy::too_many_arguments)]
+    fn with_board(
+        game_id: String,
+        black_player_id: String,
+        black_player_name: String,
+        white_player_id: String,
+        white_player_name: String,
+        config: GameConfig,
+        board: Board,
+        side_to_move: DiskColor,
+    ) -> Self {
+        let clock = Clock::new(config.time_control).map(|mut clock| {
+            clock.start(side_to_move, Instant::now());
+            clock
+        });
 
         Self {
             game_id,
@@ -92,222 +531,316 @@ impl Game {
             black_player_name,
             white_player_id,
             white_player_name,
+            created_at: Utc::now(),
+            opening: None,
+            config,
+            handicap: None,
+            clock,
+            initial_board: board,
+            initial_color: side_to_move,
             board,
-            current_color: DiskColor::Black,
-            is_game_over: false,
-            winner: None,
+            board_hash: zobrist::board_key(&board),
+            current_color: side_to_move,
+            result: None,
             move_history: Vec::new(),
+            hints: Vec::new(),
         }
     }
 
-    pub fn get_state(&self) -> GameState {
-        let mut black_count = 0;
-        let mut white_count = 0;
+    /// 盤面文字列（`X`=黒、`O`=白、`-`=空き、`#`=穴）と手番から任意の局面のゲームを作成
+    ///
+    /// 手番側が打てない場合はパスを記録し、両者とも打てない場合は終局とする
+    pub fn from_position(
+        game_id: String,
+        black_player_id: String,
+        black_player_name: String,
+        white_player_id: String,
+        white_player_name: String,
+        board: &str,
+        side_to_move: DiskColor,
+    ) -> Result<Self, String> {
+        let board = notation::parse_board_string(board)?;
+        let config = GameConfig {
+            board_size: board.size(),
+            ..GameConfig::default()
+        };
+        Self::from_board(
+            game_id,
+            black_player_id,
+            black_player_name,
+            white_player_id,
+            white_player_name,
+            config,
+            board,
+            side_to_move,
+        )
+    }
 
-        let board_strings = self
-            .board
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|cell| match cell {
-                        Some(DiskColor::Black) => {
-                            black_count += 1;
-                            Some("black".to_string())
-                        }
-                        Some(DiskColor::White) => {
-                            white_count += 1;
-                            Some("white".to_string())
-                        }
-                        None => None,
-                    })
-                    .collect()
-            })
-            .collect();
+    /// 盤面と手番から任意の局面のゲームを作成
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_board(
+        game_id: String,
+        black_player_id: String,
+        black_player_name: String,
+        white_player_id: String,
+        white_player_name: String,
+        config: GameConfig,
+        board: Board,
+        side_to_move: DiskColor,
+    ) -> Result<Self, String> {
+        config.validate()?;
+        if config.board_size != board.size() {
+            return Err(format!(
+                "Board size mismatch: config {} but board {}",
+                config.board_size,
+                board.size()
+            ));
+        }
 
-        let black_can_move = self.can_player_move(&DiskColor::Black);
-        let white_can_move = self.can_player_move(&DiskColor::White);
+        // 中央の4マスは常に石が置かれている
+        let low = board.size() / 2 - 1;
+        let high = board.size() / 2;
+        for (x, y) in [(low, low), (high, low), (low, high), (high, high)] {
+            if board.get(x, y).is_none() {
+                return Err(format!(
+                    "Invalid position: center square {} is empty",
+                    notation::format_square(x, y)
+                ));
+            }
+        }
 
-        let current_player = match self.current_color {
-            DiskColor::Black => "black".to_string(),
-            DiskColor::White => "white".to_string(),
-        };
+        let mut game = Self::with_board(
+            game_id,
+            black_player_id,
+            black_player_name,
+            white_player_id,
+            white_player_name,
+            config,
+            board,
+            side_to_move,
+        );
 
-        GameState {
-            board: board_strings,
-            current_player,
-            is_game_over: self.is_game_over,
-            winner: self.winner.clone(),
-            black_count,
-            white_count,
-            black_can_move,
-            white_can_move,
+        // パスと終局を判定
+        game.settle_turn();
+        if !game.is_over()
+            && let Some(clock) = &mut game.clock
+        {
+            clock.start(game.current_color, Instant::now());
         }
+
+        Ok(game)
     }
 
-    fn can_player_move(&self, color: &DiskColor) -> bool {
-        for y in 0..BOARD_SIZE {
-            for x in 0..BOARD_SIZE {
-                if self.is_valid_move(x, y, color) {
-                    return true;
-                }
-            }
-        }
-        false
+    /// ゲームの設定
+    pub fn config(&self) -> &GameConfig {
+        &self.config
     }
 
-    fn is_valid_move(&self, x: usize, y: usize, color: &DiskColor) -> bool {
-        if self.board[y][x].is_some() {
-            return false;
-        }
+    /// 置き石（ハンディキャップなしの場合はNone）
+    pub fn handicap(&self) -> Option<Handicap> {
+        self.handicap
+    }
 
-        for (dx, dy) in &DIRECTIONS {
-            let mut nx = x as i32 + dx;
-            let mut ny = y as i32 + dy;
+    /// 現在の盤面
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
 
-            // 盤外なら次の方向へ
-            if nx < 0 || nx >= BOARD_SIZE as i32 || ny < 0 || ny >= BOARD_SIZE as i32 {
-                continue;
-            }
+    /// 現在の手番
+    pub fn current_color(&self) -> DiskColor {
+        self.current_color
+    }
 
-            // 隣が相手の色でなければ次の方向へ
-            match &self.board[ny as usize][nx as usize] {
-                Some(c) if c != color => {}
-                _ => continue,
-            }
+    /// 開始局面の盤面
+    pub fn initial_board(&self) -> &Board {
+        &self.initial_board
+    }
 
-            nx += dx;
-            ny += dy;
-
-            // 盤外に出るまで進む
-            while nx >= 0 && nx < BOARD_SIZE as i32 && ny >= 0 && ny < BOARD_SIZE as i32 {
-                match &self.board[ny as usize][nx as usize] {
-                    // 自分の色を見つけたら有効
-                    Some(c) if c == color => return true,
-                    // 相手の色なら進み続ける
-                    Some(_) => {
-                        nx += dx;
-                        ny += dy;
-                    }
-                    // 空白なら無効（連続していない）
-                    None => break,
-                }
-            }
+    /// 開始局面の手番
+    pub fn initial_color(&self) -> DiskColor {
+        self.initial_color
+    }
+
+    /// 現在の局面（盤面と手番）のZobristキー
+    pub fn position_key(&self) -> u64 {
+        self.board_hash ^ zobrist::side_key(self.current_color)
+    }
+
+    /// 対称な局面で共通となる現在の局面のZobristキー
+    ///
+    /// 盤面の8通りの変換が必要なため、`get_state`では計算せずメッセージを作るときにだけ使う
+    pub fn canonical_key(&self) -> u64 {
+        zobrist::canonical_key(&self.board, self.current_color).0
+    }
+
+    /// 両プレイヤーの残り時間（時間制限なしの場合はNone）
+    pub fn clock_state(&self) -> Option<ClockState> {
+        self.clock.as_ref().map(|clock| clock.state(Instant::now()))
+    }
+
+    /// 終局しているか
+    pub fn is_over(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// 対局の結果（未終了の場合はNone）
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    /// 手番側が打てるマスの一覧（終局後は空）
+    pub fn legal_moves(&self) -> Vec<(usize, usize)> {
+        if self.is_over() {
+            return Vec::new();
         }
+        self.board.legal_moves(self.current_color)
+    }
 
-        false
+    /// 終局時のスコア（未終了の場合はNone）
+    pub fn final_score(&self) -> Option<FinalScore> {
+        self.result.map(|result| result.score)
     }
 
-    /// 石を反転させる
-    fn flip_disks(&mut self, x: usize, y: usize, color: &DiskColor) {
-        for (dx, dy) in &DIRECTIONS {
-            let mut to_flip = Vec::new();
-            let mut nx = x as i32 + dx;
-            let mut ny = y as i32 + dy;
+    /// 勝者の石の色（未終了または引き分けの場合はNone）
+    pub fn winner_color(&self) -> Option<DiskColor> {
+        self.result.and_then(|result| result.winner)
+    }
 
-            // 盤外なら次の方向へ
-            if nx < 0 || nx >= BOARD_SIZE as i32 || ny < 0 || ny >= BOARD_SIZE as i32 {
-                continue;
-            }
+    pub fn get_state(&self) -> GameState {
+        let board_size = self.board.size();
+        let board_strings = (0..board_size)
+            .map(|y| {
+                (0..board_size)
+                    .map(|x| self.board.get(x, y).map(|color| color.to_string()))
+                    .collect()
+            })
+            .collect();
 
-            // 隣が相手の色でなければ次の方向へ
-            match &self.board[ny as usize][nx as usize] {
-                Some(c) if c != color => {
-                    to_flip.push((nx as usize, ny as usize));
-                }
-                _ => continue,
-            }
+        // パスを除く最後の手
+        let (last_move, flipped) = self
+            .move_history
+            .iter()
+            .rev()
+            .find_map(|game_move| match game_move.action {
+                MoveAction::Place { x, y } => Some((Some((x, y)), game_move.flipped.clone())),
+                MoveAction::Pass => None,
+            })
+            .unwrap_or_default();
 
-            // さらに進む
-            nx += dx;
-            ny += dy;
-
-            // 盤外に出るまで進む
-            while nx >= 0 && nx < BOARD_SIZE as i32 && ny >= 0 && ny < BOARD_SIZE as i32 {
-                match &self.board[ny as usize][nx as usize] {
-                    // 自分の色を見つけたら挟まれた石を反転
-                    Some(c) if c == color => {
-                        for (fx, fy) in to_flip {
-                            self.board[fy][fx] = Some(color.clone());
-                        }
-                        break;
-                    }
-                    // 相手の色なら反転候補に追加して進む
-                    Some(_) => {
-                        to_flip.push((nx as usize, ny as usize));
-                        nx += dx;
-                        ny += dy;
-                    }
-                    // 空白なら無効（連続していない）
-                    None => break,
-                }
-            }
+        // 手番側の合法手は1度だけ求め、打てるかどうかもそこから決める
+        let moves = self.board.legal_moves(self.current_color);
+        let current_can_move = !moves.is_empty();
+        let other_can_move = self.board.has_legal_move(self.current_color.opposite());
+        let (black_can_move, white_can_move) = match self.current_color {
+            DiskColor::Black => (current_can_move, other_can_move),
+            DiskColor::White => (other_can_move, current_can_move),
+        };
+
+        GameState {
+            board: board_strings,
+            board_size,
+            variant: self.config.variant,
+            current_player: self.current_color.to_string(),
+            is_game_over: self.is_over(),
+            winner: self
+                .winner_color()
+                .map(|color| self.player_id(color).to_string()),
+            black_count: self.board.count(DiskColor::Black),
+            white_count: self.board.count(DiskColor::White),
+            black_can_move,
+            white_can_move,
+            legal_moves: if self.is_over() { Vec::new() } else { moves },
+            holes: self.board.holes(),
+            last_move,
+            flipped,
+            position_key: self.position_key(),
+            clock: self.clock_state(),
         }
     }
 
     /// 次のプレイヤーに変更
     fn next_player(&mut self) {
-        self.current_color = match self.current_color {
-            DiskColor::Black => DiskColor::White,
-            DiskColor::White => DiskColor::Black,
-        };
+        self.current_color = self.current_color.opposite();
+        self.settle_turn();
+    }
 
+    /// 手番のプレイヤーが打てなければパスを記録し、両者とも打てなければ終局させる
+    fn settle_turn(&mut self) {
         // 次のプレイヤーが手を打てなければスキップ
-        if !self.can_player_move(&self.current_color) {
+        if !self.board.has_legal_move(self.current_color) {
+            // もう一方も打てなければゲーム終了
+            if !self.board.has_legal_move(self.current_color.opposite()) {
+                self.end_game();
+                return;
+            }
+
             debug_log!(
                 "Player {} has no valid moves. Skipping turn.",
                 self.current_color.to_string()
             );
 
-            // 反対の色に戻す
+            // パスを履歴に追加して反対の色に戻す
+            self.move_history.push(GameMove {
+                player_id: self.player_id(self.current_color).to_string(),
+                action: MoveAction::Pass,
+                color: self.current_color,
+                flipped: Vec::new(),
+            });
             self.current_color = self.current_color.opposite();
-
-            // もう一方も打てなければゲーム終了
-            if !self.can_player_move(&self.current_color) {
-                self.end_game();
-            }
         }
     }
 
     /// ゲームを終了させる
     fn end_game(&mut self) {
         // 石の数をカウント
-        let mut black_count = 0;
-        let mut white_count = 0;
-
-        for row in &self.board {
-            for cell in row {
-                match cell {
-                    Some(DiskColor::Black) => black_count += 1,
-                    Some(DiskColor::White) => white_count += 1,
-                    None => {}
-                }
+        let black_count = self.board.count(DiskColor::Black);
+        let white_count = self.board.count(DiskColor::White);
+
+        // ルールに従って勝者を決定
+        let result = self.finish(
+            Termination::Normal,
+            self.config.variant.winner(black_count, white_count),
+        );
+
+        match result.winner {
+            Some(color) => {
+                info_log!(
+                    "Game over ({}): {} won {} ({} black vs {} white)",
+                    self.config.variant,
+                    self.player_name(color),
+                    result.score,
+                    black_count,
+                    white_count
+                );
+            }
+            None => {
+                // 引き分け
+                info_log!("Game over: Draw with {} disks each", black_count);
             }
         }
+    }
 
-        // 勝者を決定
-        self.is_game_over = true;
+    /// 時計を止め、終局の理由に応じたスコアで結果を記録する
+    fn finish(&mut self, termination: Termination, winner: Option<DiskColor>) -> GameResult {
+        self.stop_clock();
 
-        if black_count > white_count {
-            self.winner = Some(self.black_player_id.clone());
-            info_log!(
-                "Game over: {} won with {} disks vs {}",
-                self.black_player_name,
-                black_count,
-                white_count
-            );
-        } else if white_count > black_count {
-            self.winner = Some(self.white_player_id.clone());
-            info_log!(
-                "Game over: {} won with {} disks vs {}",
-                self.white_player_name,
-                white_count,
-                black_count
-            );
-        } else {
-            // 引き分け
-            self.winner = None;
-            info_log!("Game over: Draw with {} disks each", black_count);
-        }
+        let score = match (termination, winner) {
+            (Termination::Normal, _) => FinalScore::completed(&self.board, self.config.variant),
+            (Termination::Timeout, Some(color)) => {
+                FinalScore::timeout(&self.board, self.config.variant, color)
+            }
+            // 投了、放棄、管理者による終了は勝者が全てのマスを得る
+            (_, Some(color)) => FinalScore::resignation(&self.board, color),
+            (_, None) => FinalScore::draw(&self.board),
+        };
+
+        let result = GameResult {
+            termination,
+            winner,
+            score,
+        };
+        self.result = Some(result);
+        result
     }
 
     /// プレイヤーIDから石の色を取得
@@ -321,87 +854,467 @@ impl Game {
         }
     }
 
+    /// 石の色からプレイヤーIDを取得
+    pub fn player_id(&self, color: DiskColor) -> &str {
+        match color {
+            DiskColor::Black => &self.black_player_id,
+            DiskColor::White => &self.white_player_id,
+        }
+    }
+
+    /// 石の色からプレイヤー名を取得
+    pub fn player_name(&self, color: DiskColor) -> &str {
+        match color {
+            DiskColor::Black => &self.black_player_name,
+            DiskColor::White => &self.white_player_name,
+        }
+    }
+
+    /// パスを含むこれまでの手の履歴
+    pub fn move_history(&self) -> &[GameMove] {
+        &self.move_history
+    }
+
     /// 手を打つ
-    pub fn make_move(&mut self, player_id: &str, x: usize, y: usize) -> Result<GameState, String> {
+    pub fn make_move(
+        &mut self,
+        player_id: &str,
+        x: usize,
+        y: usize,
+    ) -> Result<GameState, GameError> {
+        let now = Instant::now();
+
         // ゲームが終了していないか確認
-        if self.is_game_over {
-            return Err("Game is already over".to_string());
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+
+        // 時間切れでないか確認（終局処理は`check_timeout`で行う）
+        if self
+            .clock
+            .as_ref()
+            .is_some_and(|clock| clock.flagged(now).is_some())
+        {
+            return Err(GameError::TimeUp);
         }
 
         // プレイヤーの色を確認
         let player_color = match self.get_player_color(player_id) {
             Some(color) => color,
-            None => return Err("You are not a player in this game".to_string()),
+            None => return Err(GameError::NotParticipant),
         };
 
         // プレイヤーのターンか確認
         if player_color != self.current_color {
-            return Err("It's not your turn".to_string());
+            return Err(GameError::NotYourTurn);
         }
 
         // 座標が有効範囲内か確認
-        if x >= BOARD_SIZE || y >= BOARD_SIZE {
-            return Err("Invalid coordinates".to_string());
+        if x >= self.board.size() || y >= self.board.size() {
+            return Err(GameError::OutOfBounds);
         }
 
-        // 手が有効か確認
-        if !self.is_valid_move(x, y, &player_color) {
-            return Err("Invalid move".to_string());
+        // 空きマスか確認
+        if self.board.get(x, y).is_some() {
+            return Err(GameError::SquareOccupied);
+        }
+        if self.board.is_hole(x, y) {
+            return Err(GameError::SquareBlocked);
         }
 
-        // 石を置く
-        self.board[y][x] = Some(player_color.clone());
+        // 石を置いて反転処理（反転できなければ無効）
+        let flipped = self.board.play(player_color, x, y);
+        if flipped.is_empty() {
+            return Err(GameError::NoFlips);
+        }
 
-        // 反転処理
-        self.flip_disks(x, y, &player_color);
+        // Zobristキーを差分更新
+        let size = self.board.size();
+        self.board_hash ^= zobrist::disc_key(player_color, y * size + x);
+        for &(fx, fy) in &flipped {
+            let index = fy * size + fx;
+            self.board_hash ^= zobrist::disc_key(player_color.opposite(), index)
+                ^ zobrist::disc_key(player_color, index);
+        }
+        debug_assert_eq!(self.board_hash, zobrist::board_key(&self.board));
 
         // 履歴に追加
         self.move_history.push(GameMove {
             player_id: player_id.to_string(),
-            x,
-            y,
+            action: MoveAction::Place { x, y },
             color: player_color,
+            flipped,
         });
 
         // 次のプレイヤーに変更
         self.next_player();
 
+        // 時計を次の手番に切り替える
+        if !self.is_over()
+            && let Some(clock) = &mut self.clock
+        {
+            clock.press(player_color, self.current_color, now);
+        }
+
         // 新しいゲーム状態を返す
         Ok(self.get_state())
     }
 
-    /// ゲームを降参する
-    pub fn resign(&mut self, player_id: &str) -> Result<GameState, String> {
-        // ゲームが終了していないか確認
-        if self.is_game_over {
-            return Err("Game is already over".to_string());
+    /// これまでに使ったヒント
+    pub fn hints(&self) -> &[HintRecord] {
+        &self.hints
+    }
+
+    /// 手番のプレイヤーがヒントを使えるか確認する（使った回数を返す）
+    ///
+    /// レーティングに反映する対局では使えず、1局で1人`MAX_HINTS`回まで使える
+    pub fn check_hint(&self, player_id: &str) -> Result<usize, GameError> {
+        let color = self.active_player_color(player_id)?;
+        if !self.config.casual {
+            return Err(GameError::HintsNotAllowed);
+        }
+        if color != self.current_color {
+            return Err(GameError::NotYourTurn);
         }
 
-        // プレイヤーの色を確認
-        if player_id != self.black_player_id && player_id != self.white_player_id {
-            return Err("You are not a player in this game".to_string());
+        let used = self.hints.iter().filter(|hint| hint.color == color).count();
+        if used >= MAX_HINTS {
+            return Err(GameError::HintLimitReached);
         }
+        Ok(used)
+    }
 
-        // 勝者を設定
-        self.is_game_over = true;
+    /// 手番のプレイヤーがヒントを使う（残りの回数を返す）
+    ///
+    /// 局面の評価が終わってから呼び、評価に失敗した場合は回数を消費しない
+    pub fn use_hint(&mut self, player_id: &str) -> Result<usize, GameError> {
+        let used = self.check_hint(player_id)?;
+        let color = self.current_color;
 
-        if player_id == self.black_player_id {
-            self.winner = Some(self.white_player_id.clone());
-            info_log!(
-                "Game over: {} resigned, {} wins",
-                self.black_player_name,
-                self.white_player_name
-            );
+        self.hints.push(HintRecord {
+            ply: self.move_history.len(),
+            color,
+        });
+        info_log!(
+            "Hint used in game {} by {} ({}/{})",
+            self.game_id,
+            color,
+            used + 1,
+            MAX_HINTS
+        );
+        Ok(MAX_HINTS - used - 1)
+    }
+
+    /// 標準座標表記（例: `f5`）で手を打つ
+    pub fn make_move_notation(
+        &mut self,
+        player_id: &str,
+        notation: &str,
+    ) -> Result<GameState, GameError> {
+        match notation::parse_action(notation, self.board.size())
+            .map_err(GameError::InvalidNotation)?
+        {
+            MoveAction::Place { x, y } => self.make_move(player_id, x, y),
+            // パスは打てる場所がない場合に自動で行われる
+            MoveAction::Pass => Err(GameError::IllegalPass),
+        }
+    }
+
+    /// これまでの手を連結した棋譜文字列（例: `f5d6c3`）
+    pub fn transcript(&self) -> String {
+        notation::format_transcript(&self.move_history)
+    }
+
+    /// ゲームを降参する
+    pub fn resign(&mut self, player_id: &str) -> Result<GameState, GameError> {
+        let color = self.active_player_color(player_id)?;
+        self.finish(Termination::Resignation, Some(color.opposite()));
+        info_log!(
+            "Game over: {} resigned, {} wins",
+            self.player_name(color),
+            self.player_name(color.opposite())
+        );
+
+        // 新しいゲーム状態を返す
+        Ok(self.get_state())
+    }
+
+    /// 対局中に切断したプレイヤーの負けとしてゲームを終了させる
+    pub fn abandon(&mut self, player_id: &str) -> Result<GameState, GameError> {
+        let color = self.active_player_color(player_id)?;
+        self.finish(Termination::Abandonment, Some(color.opposite()));
+        info_log!(
+            "Game over: {} abandoned the game, {} wins",
+            self.player_name(color),
+            self.player_name(color.opposite())
+        );
+
+        Ok(self.get_state())
+    }
+
+    /// 対局者の操作以外の理由（合意の引き分け、管理者による終了、棋譜の読み込みなど）で
+    /// ゲームを終了させる
+    ///
+    /// 通常の終局を指定した場合は`winner`を無視して盤面から勝者を決める
+    pub fn conclude(
+        &mut self,
+        termination: Termination,
+        winner: Option<DiskColor>,
+    ) -> Result<GameState, GameError> {
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+
+        if termination == Termination::Normal {
+            self.end_game();
         } else {
-            self.winner = Some(self.black_player_id.clone());
+            let result = self.finish(termination, winner);
             info_log!(
-                "Game over: {} resigned, {} wins",
-                self.white_player_name,
-                self.black_player_name
+                "Game over ({}): {} {}",
+                termination,
+                self.game_id,
+                result.score
             );
         }
 
-        // 新しいゲーム状態を返す
         Ok(self.get_state())
     }
+
+    /// 手番側の持ち時間が切れていれば時間切れ負けで終局させ、新しいゲーム状態を返す
+    pub fn check_timeout(&mut self, now: Instant) -> Option<GameState> {
+        if self.is_over() {
+            return None;
+        }
+        let flagged = self.clock.as_ref()?.flagged(now)?;
+
+        self.finish(Termination::Timeout, Some(flagged.opposite()));
+        info_log!(
+            "Game over: {} ran out of time, {} wins",
+            self.player_name(flagged),
+            self.player_name(flagged.opposite())
+        );
+
+        Some(self.get_state())
+    }
+
+    /// 未終了のゲームの対局者の色を取得
+    fn active_player_color(&self, player_id: &str) -> Result<DiskColor, GameError> {
+        // ゲームが終了していないか確認
+        if self.is_over() {
+            return Err(GameError::GameOver);
+        }
+
+        // プレイヤーの色を確認
+        self.get_player_color(player_id)
+            .ok_or(GameError::NotParticipant)
+    }
+
+    /// 対局時計を止める
+    fn stop_clock(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            clock.stop(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 左上から順に黒`black`個、白`white`個を並べ、最後の`holes`マスを穴にした8x8の盤面
+    fn board(black: usize, white: usize, holes: usize) -> Board {
+        let mut board = Board::empty(8);
+        for index in 0..black + white {
+            let color = if index < black {
+                DiskColor::Black
+            } else {
+                DiskColor::White
+            };
+            board.set(index % 8, index / 8, Some(color));
+        }
+        for index in 64 - holes..64 {
+            board.set_hole(index % 8, index / 8);
+        }
+        board
+    }
+
+    fn score(black: usize, white: usize) -> FinalScore {
+        FinalScore { black, white }
+    }
+
+    #[test]
+    fn completed_game_gives_empties_to_winner() {
+        assert_eq!(
+            FinalScore::completed(&board(40, 20, 0), Variant::Standard),
+            score(44, 20)
+        );
+        assert_eq!(
+            FinalScore::completed(&board(20, 40, 0), Variant::Standard),
+            score(20, 44)
+        );
+        assert_eq!(
+            FinalScore::completed(&board(64, 0, 0), Variant::Standard),
+            score(64, 0)
+        );
+    }
+
+    #[test]
+    fn drawn_game_splits_empties() {
+        assert_eq!(
+            FinalScore::completed(&board(30, 30, 0), Variant::Standard),
+            score(32, 32)
+        );
+        // 空きマスが奇数の場合は白に1つ多く与える
+        assert_eq!(
+            FinalScore::completed(&board(30, 30, 1), Variant::Standard),
+            score(31, 32)
+        );
+        assert_eq!(FinalScore::draw(&board(40, 20, 0)), score(32, 32));
+    }
+
+    #[test]
+    fn anti_reversi_counts_opponent_discs() {
+        // 石の少ない白の勝ちで、白の得点は黒の石の数と空きマス
+        let result = FinalScore::completed(&board(40, 20, 0), Variant::AntiReversi);
+        assert_eq!(result, score(20, 44));
+        assert_eq!(result.margin(), -24);
+    }
+
+    #[test]
+    fn holes_are_not_scored() {
+        let result = FinalScore::completed(&board(30, 20, 4), Variant::Standard);
+        assert_eq!(result, score(40, 20));
+        assert_eq!(result.black + result.white, 60);
+    }
+
+    #[test]
+    fn resignation_and_timeout_scores() {
+        let position = board(20, 40, 0);
+        assert_eq!(
+            FinalScore::resignation(&position, DiskColor::Black),
+            score(64, 0)
+        );
+        // 勝者が上回っていれば空きマスを与えるだけ
+        assert_eq!(
+            FinalScore::timeout(&position, Variant::Standard, DiskColor::White),
+            score(20, 44)
+        );
+        // 勝者が下回っていれば最小差の勝ち
+        assert_eq!(
+            FinalScore::timeout(&position, Variant::Standard, DiskColor::Black),
+            score(33, 31)
+        );
+    }
+
+    #[test]
+    fn position_without_moves_is_scored_immediately() {
+        // 黒だけの盤面はどちらも打てずに終局する
+        let text = format!("{}{}", "X".repeat(40), "-".repeat(24));
+        let game = Game::from_position(
+            "wof".to_string(),
+            "black-id".to_string(),
+            "black".to_string(),
+            "white-id".to_string(),
+            "white".to_string(),
+            &text,
+            DiskColor::White,
+        )
+        .unwrap();
+
+        let result = game.result().unwrap();
+        assert_eq!(result.termination, Termination::Normal);
+        assert_eq!(result.winner, Some(DiskColor::Black));
+        assert_eq!(result.score, score(64, 0));
+        assert_eq!(result.outcome(DiskColor::Black), 1.0);
+    }
+
+    /// 黒`black-id`と白`white-id`の初期配置の対局
+    fn new_game(casual: bool) -> Game {
+        let config = GameConfig {
+            casual,
+            ..GameConfig::default()
+        };
+        Game::new(
+            "hint".to_string(),
+            "black-id".to_string(),
+            "black".to_string(),
+            "white-id".to_string(),
+            "white".to_string(),
+            config,
+            None,
+        )
+    }
+
+    #[test]
+    fn hints_are_limited_to_casual_games_on_your_turn() {
+        let mut rated = new_game(false);
+        assert_eq!(
+            rated.check_hint("black-id"),
+            Err(GameError::HintsNotAllowed)
+        );
+        assert_eq!(rated.use_hint("black-id"), Err(GameError::HintsNotAllowed));
+
+        let casual = new_game(true);
+        assert_eq!(casual.check_hint("white-id"), Err(GameError::NotYourTurn));
+        assert_eq!(casual.check_hint("someone"), Err(GameError::NotParticipant));
+        assert!(casual.hints().is_empty());
+    }
+
+    #[test]
+    fn only_recorded_hints_count_towards_the_limit() {
+        let mut game = new_game(true);
+
+        // 確認だけでは回数を消費しない
+        for _ in 0..=MAX_HINTS {
+            assert_eq!(game.check_hint("black-id"), Ok(0));
+        }
+
+        for used in 0..MAX_HINTS {
+            assert_eq!(game.use_hint("black-id"), Ok(MAX_HINTS - used - 1));
+        }
+        assert_eq!(
+            game.check_hint("black-id"),
+            Err(GameError::HintLimitReached)
+        );
+        assert_eq!(game.use_hint("black-id"), Err(GameError::HintLimitReached));
+        assert_eq!(game.hints().len(), MAX_HINTS);
+        assert!(game.hints().iter().all(|hint| hint.ply == 0));
+
+        // 相手の回数は別に数える
+        game.make_move("black-id", 5, 4).unwrap();
+        assert_eq!(game.check_hint("white-id"), Ok(0));
+    }
+
+    #[test]
+    fn state_reports_moves_for_both_colors() {
+        let state = new_game(true).get_state();
+        assert!(state.black_can_move && state.white_can_move);
+        assert_eq!(state.legal_moves.len(), 4);
+
+        // 白の石は辺の端にしかなく、白だけが打てない
+        let (edge, empty, center) = ("-OXXXXXX", "-".repeat(16), "---XX---".repeat(2));
+        let text = format!("{}{}{}{}{}", edge, empty, center, empty, edge);
+        let game = Game::from_position(
+            "state".to_string(),
+            "black-id".to_string(),
+            "black".to_string(),
+            "white-id".to_string(),
+            "white".to_string(),
+            &text,
+            DiskColor::Black,
+        )
+        .unwrap();
+        let state = game.get_state();
+        assert!(state.black_can_move);
+        assert!(!state.white_can_move);
+        assert_eq!(state.legal_moves, [(0, 0), (0, 7)]);
+
+        // 終局後は合法手を返さない
+        let mut game = new_game(true);
+        game.resign("black-id").unwrap();
+        let state = game.get_state();
+        assert!(state.is_game_over);
+        assert!(state.legal_moves.is_empty());
+        assert!(state.black_can_move && state.white_can_move);
+    }
 }
@@ -0,0 +1,497 @@
+//! # GGF（Generic Game Format）
+//!
+//! GGSや多くのオセロ棋譜データベースで使われる形式で、ゲームの書き出しと読み込みを行う
+//!
+//! ```text
+//! (;GM[Othello]PC[reversi]DT[2025.01.01_12:00:00.GMT]PB[alice]PW[bob]RE[+4.000]TY[8]
+//! BO[8 -------- -------- -------- ---O*--- ---*O--- -------- -------- -------- *]B[f5]W[d6];)
+//! ```
+//!
+//! XOTやランダム開始のゲームでは、`BO`の開始局面に加えて独自の`OP`プロパティに
+//! 開始局面の種類と初期配置からの手順を記録する（例: `OP[xot:f5d6c3d3c4f4f6f3]`）。
+//! 穴のある盤面では`BO`の穴のマスを`#`で表す。
+//! プロパティの値に含まれる`]`と`\`は`\`でエスケープする（例: `PB[a\]b]`）
+//!
+//! ## 関数
+//!
+//! - `export_game`:  ゲームをGGF文字列に変換
+//! - `import_games`: GGF文字列（複数ゲーム可）からゲームを復元
+
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::bitboard::Board;
+use crate::game::{DiskColor, Game, GameConfig, MoveAction, Termination, Variant};
+use crate::notation;
+use crate::opening::StartPosition;
+
+/// 対局場所として記録する名前
+const PLACE: &str = "reversi";
+/// 日時の書式
+const DATE_FORMAT: &str = "%Y.%m.%d_%H:%M:%S.GMT";
+
+/// ゲームをGGF文字列に変換
+pub fn export_game(game: &Game) -> String {
+    let board = game.initial_board();
+    let mut ggf = String::from("(;GM[Othello]");
+
+    ggf.push_str(&format!("PC[{}]", PLACE));
+    ggf.push_str(&format!("DT[{}]", game.created_at.format(DATE_FORMAT)));
+    ggf.push_str(&format!("PB[{}]", escape(&game.black_player_name)));
+    ggf.push_str(&format!("PW[{}]", escape(&game.white_player_name)));
+    if let Some(result) = format_result(game) {
+        ggf.push_str(&format!("RE[{}]", result));
+    }
+    ggf.push_str(&format!("TY[{}]", format_type(game)));
+    ggf.push_str(&format!(
+        "BO[{}]",
+        format_board(board, game.initial_color())
+    ));
+    if let Some(opening) = &game.opening {
+        ggf.push_str(&format!("OP[{}:{}]", game.config().start, opening));
+    }
+
+    for game_move in game.move_history() {
+        let tag = match game_move.color {
+            DiskColor::Black => "B",
+            DiskColor::White => "W",
+        };
+        let action = match game_move.action {
+            MoveAction::Pass => "PA".to_string(),
+            action => notation::format_action(&action),
+        };
+        ggf.push_str(&format!("{}[{}]", tag, action));
+    }
+
+    ggf.push_str(";)");
+    ggf
+}
+
+/// GGF文字列からゲームを復元（複数ゲームを含んでいてもよい）
+pub fn import_games(text: &str) -> Result<Vec<Game>, String> {
+    let mut games = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("(;") {
+        let end = find_game_end(&rest[start..]).ok_or("Unterminated GGF game: missing ';)'")?;
+        games.push(import_game(&rest[start + 2..start + end])?);
+        rest = &rest[start + end + 2..];
+    }
+
+    if games.is_empty() {
+        return Err("No GGF game found".to_string());
+    }
+
+    Ok(games)
+}
+
+/// ゲームの終わりの`;)`の位置（プロパティの値の中は読み飛ばす）
+fn find_game_end(text: &str) -> Option<usize> {
+    let mut in_value = false;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' if in_value => {
+                chars.next();
+            }
+            '[' => in_value = true,
+            ']' => in_value = false,
+            ';' if !in_value && chars.peek().is_some_and(|&(_, next)| next == ')') => {
+                return Some(index);
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// 1ゲーム分（`(;`と`;)`の間）を読み込む
+fn import_game(body: &str) -> Result<Game, String> {
+    let properties = parse_properties(body)?;
+    let property = |key: &str| {
+        properties
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+
+    if let Some(kind) = property("GM")
+        && !kind.eq_ignore_ascii_case("othello")
+    {
+        return Err(format!("Unsupported game type: {}", kind));
+    }
+
+    // 盤面サイズと開始局面
+    let (board, color) = match property("BO") {
+        Some(value) => parse_board(value)?,
+        None => {
+            let size = match property("TY") {
+                Some(value) => parse_board_size(value)?,
+                None => crate::bitboard::DEFAULT_BOARD_SIZE,
+            };
+            (Board::initial(size), DiskColor::Black)
+        }
+    };
+    let opening = property("OP").map(parse_opening).transpose()?;
+    let config = GameConfig {
+        board_size: board.size(),
+        variant: property("TY").map(parse_variant).unwrap_or_default(),
+        start: opening
+            .as_ref()
+            .map(|(start, _)| *start)
+            .unwrap_or_default(),
+        // 穴は`BO`の盤面から復元する
+        ..GameConfig::default()
+    };
+
+    let game_id = Uuid::new_v4().to_string();
+    let mut game = Game::from_board(
+        game_id.clone(),
+        format!("{}:black", game_id),
+        property("PB").unwrap_or("Black").to_string(),
+        format!("{}:white", game_id),
+        property("PW").unwrap_or("White").to_string(),
+        config,
+        board,
+        color,
+    )?;
+    if let Some(date) = property("DT").and_then(parse_date) {
+        game.created_at = date;
+    }
+    game.opening = opening.map(|(_, transcript)| transcript);
+
+    // 手を再生
+    for (key, value) in &properties {
+        let color = match key.as_str() {
+            "B" => DiskColor::Black,
+            "W" => DiskColor::White,
+            _ => continue,
+        };
+        let token = value.split('/').next().unwrap_or_default();
+        let ply = game.move_history().len() + 1;
+
+        match notation::parse_action(token, board.size())? {
+            MoveAction::Place { x, y } => {
+                let player_id = game.player_id(color).to_string();
+                game.make_move(&player_id, x, y)
+                    .map_err(|e| format!("Illegal move {} at ply {}: {}", token, ply, e))?;
+            }
+            // パスはゲーム側で自動的に記録されているので整合性のみ確認
+            MoveAction::Pass => {
+                if game.is_over() {
+                    continue;
+                }
+                let passed = game
+                    .move_history()
+                    .last()
+                    .is_some_and(|m| m.action == MoveAction::Pass && m.color == color);
+                if !passed {
+                    return Err(format!("Unexpected pass by {} at ply {}", color, ply));
+                }
+            }
+        }
+    }
+
+    // 投了や時間切れなどで途中終了したゲーム
+    if !game.is_over()
+        && let Some((termination, winner)) = property("RE").and_then(parse_result)
+    {
+        game.conclude(termination, winner)?;
+    }
+
+    Ok(game)
+}
+
+/// `KEY[value]`の並びを読み込む（値の中の`\`でエスケープされた文字は元に戻す）
+fn parse_properties(body: &str) -> Result<Vec<(String, String)>, String> {
+    let mut properties = Vec::new();
+    let mut rest = body.trim_start();
+
+    while !rest.is_empty() {
+        let open = rest
+            .find('[')
+            .ok_or_else(|| format!("Malformed GGF property near '{}'", rest))?;
+        let key = rest[..open].trim().to_ascii_uppercase();
+
+        let mut value = String::new();
+        let mut chars = rest[open + 1..].char_indices();
+        let close = loop {
+            match chars.next() {
+                Some((_, '\\')) => {
+                    if let Some((_, c)) = chars.next() {
+                        value.push(c);
+                    }
+                }
+                Some((index, ']')) => break open + 1 + index,
+                Some((_, c)) => value.push(c),
+                None => return Err("Malformed GGF property: missing ']'".to_string()),
+            }
+        };
+        properties.push((key, value.trim().to_string()));
+
+        rest = rest[close + 1..].trim_start();
+    }
+
+    Ok(properties)
+}
+
+/// プロパティの値に含まれる`]`と`\`をエスケープする
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, ']' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 盤面をGGFの`BO`形式に変換（例: `8 -------- ... *`）
+fn format_board(board: &Board, color: DiskColor) -> String {
+    let size = board.size();
+    let rows: Vec<String> = (0..size)
+        .map(|y| {
+            (0..size)
+                .map(|x| match board.get(x, y) {
+                    Some(DiskColor::Black) => '*',
+                    Some(DiskColor::White) => 'O',
+                    None if board.is_hole(x, y) => '#',
+                    None => '-',
+                })
+                .collect()
+        })
+        .collect();
+
+    format!("{} {} {}", size, rows.join(" "), format_color(color))
+}
+
+/// GGFの`BO`形式を盤面と手番に変換
+fn parse_board(value: &str) -> Result<(Board, DiskColor), String> {
+    let mut tokens = value.split_whitespace();
+    let size = parse_board_size(tokens.next().unwrap_or_default())?;
+    let cells: Vec<char> = tokens.flat_map(|token| token.chars()).collect();
+
+    if cells.len() != size * size + 1 {
+        return Err(format!("Invalid GGF board: {}", value));
+    }
+
+    let mut board = Board::empty(size);
+    for (index, cell) in cells[..size * size].iter().enumerate() {
+        let (x, y) = (index % size, index / size);
+        let disk = match cell {
+            '*' | 'x' | 'X' => Some(DiskColor::Black),
+            'O' | 'o' => Some(DiskColor::White),
+            '-' | '.' => None,
+            '#' => {
+                board.set_hole(x, y);
+                continue;
+            }
+            _ => return Err(format!("Invalid GGF board cell: {}", cell)),
+        };
+        board.set(x, y, disk);
+    }
+
+    let color = match cells[size * size] {
+        '*' | 'x' | 'X' => DiskColor::Black,
+        'O' | 'o' => DiskColor::White,
+        c => return Err(format!("Invalid GGF side to move: {}", c)),
+    };
+
+    Ok((board, color))
+}
+
+/// `TY`または`BO`の盤面サイズを読み込む（例: `8`、`10`、`s8r20`の`8`）
+fn parse_board_size(value: &str) -> Result<usize, String> {
+    let digits: String = value
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    let size = digits
+        .parse()
+        .map_err(|_| format!("Invalid GGF board size: {}", value))?;
+    GameConfig {
+        board_size: size,
+        ..GameConfig::default()
+    }
+    .validate()?;
+    Ok(size)
+}
+
+/// ゲームの種類（例: `8`、アンチリバーシは`a8`）
+fn format_type(game: &Game) -> String {
+    let config = game.config();
+    match config.variant {
+        Variant::Standard => config.board_size.to_string(),
+        Variant::AntiReversi => format!("a{}", config.board_size),
+    }
+}
+
+/// ゲームの種類からルールを判定
+fn parse_variant(value: &str) -> Variant {
+    if value.contains('a') {
+        Variant::AntiReversi
+    } else {
+        Variant::Standard
+    }
+}
+
+/// `OP`プロパティ（例: `xot:f5d6c3d3c4f4f6f3`）を開始局面の種類と手順に変換
+fn parse_opening(value: &str) -> Result<(StartPosition, String), String> {
+    let (start, transcript) = value
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid GGF opening: {}", value))?;
+    let start = StartPosition::parse(start)
+        .ok_or_else(|| format!("Unknown GGF start position: {}", start))?;
+    Ok((start, transcript.trim().to_string()))
+}
+
+fn format_color(color: DiskColor) -> char {
+    match color {
+        DiskColor::Black => '*',
+        DiskColor::White => 'O',
+    }
+}
+
+/// 黒から見た結果（例: `+4.000`）
+///
+/// 得点差はWOFルールのスコア（空きマスは勝者に与える）から計算し、途中で終了したゲームには
+/// 終局の理由（投了・放棄・管理者による終了は`:r`、時間切れは`:t`、合意は`:s`）を付ける
+fn format_result(game: &Game) -> Option<String> {
+    let result = game.result()?;
+    let suffix = match result.termination {
+        Termination::Normal => "",
+        Termination::Timeout => ":t",
+        Termination::AgreedDraw => ":s",
+        Termination::Resignation | Termination::Abandonment | Termination::Admin => {
+            if result.winner.is_some() { ":r" } else { ":s" }
+        }
+    };
+    Some(format!("{:+.3}{}", result.score.margin() as f64, suffix))
+}
+
+/// 途中で終了したゲームの結果から終局の理由と勝者（引き分けはNone）を読み取る
+///
+/// 時間切れと合意の引き分け以外は投了として扱い、勝者を決められない場合はNone
+fn parse_result(value: &str) -> Option<(Termination, Option<DiskColor>)> {
+    let (score, suffix) = value.split_once(':').unwrap_or((value, "r"));
+    let score: f64 = score.trim().parse().ok()?;
+    let winner = if score > 0.0 {
+        Some(DiskColor::Black)
+    } else if score < 0.0 {
+        Some(DiskColor::White)
+    } else {
+        None
+    };
+
+    let termination = match (suffix.trim(), winner) {
+        ("t", Some(_)) => Termination::Timeout,
+        ("s", None) => Termination::AgreedDraw,
+        (_, Some(_)) => Termination::Resignation,
+        (_, None) => return None,
+    };
+    Some((termination, winner))
+}
+
+fn parse_date(value: &str) -> Option<chrono::DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, DATE_FORMAT)
+        .ok()
+        .map(|date| date.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_game(black: &str, white: &str) -> Game {
+        Game::new(
+            "game".to_string(),
+            "black-id".to_string(),
+            black.to_string(),
+            "white-id".to_string(),
+            white.to_string(),
+            GameConfig::default(),
+            None,
+        )
+    }
+
+    /// 合法手の先頭を打ち続けて終局させる
+    fn play_out(game: &mut Game) {
+        while let Some(&(x, y)) = game.legal_moves().first() {
+            let player_id = game.player_id(game.current_color()).to_string();
+            game.make_move(&player_id, x, y).unwrap();
+        }
+    }
+
+    fn import_one(text: &str) -> Game {
+        let mut games = import_games(text).unwrap();
+        assert_eq!(games.len(), 1);
+        games.pop().unwrap()
+    }
+
+    #[test]
+    fn finished_game_round_trip() {
+        let mut game = new_game("alice", "bob");
+        play_out(&mut game);
+        assert!(game.is_over());
+
+        let ggf = export_game(&game);
+        let imported = import_one(&ggf);
+        assert_eq!(imported.transcript(), game.transcript());
+        assert_eq!(imported.move_history().len(), game.move_history().len());
+        assert_eq!(imported.board(), game.board());
+        assert_eq!(imported.result(), game.result());
+        assert_eq!(imported.black_player_name, "alice");
+        assert_eq!(imported.white_player_name, "bob");
+        assert_eq!(export_game(&imported), ggf);
+    }
+
+    #[test]
+    fn resigned_game_round_trip() {
+        let mut game = new_game("alice", "bob");
+        game.make_move_notation("black-id", "f5").unwrap();
+        game.resign("white-id").unwrap();
+
+        let ggf = export_game(&game);
+        assert!(ggf.contains("RE[+"), "{}", ggf);
+        assert!(ggf.contains(":r]"), "{}", ggf);
+
+        let imported = import_one(&ggf);
+        let result = imported.result().unwrap();
+        assert_eq!(result.termination, Termination::Resignation);
+        assert_eq!(result.winner, Some(DiskColor::Black));
+        assert_eq!(imported.transcript(), "f5");
+    }
+
+    #[test]
+    fn player_names_are_escaped() {
+        let mut game = new_game("a]TY[a8", "back\\slash;)");
+        game.make_move_notation("black-id", "f5").unwrap();
+
+        let ggf = export_game(&game);
+        assert!(ggf.contains("PB[a\\]TY[a8]"), "{}", ggf);
+
+        let imported = import_one(&ggf);
+        assert_eq!(imported.black_player_name, "a]TY[a8");
+        assert_eq!(imported.white_player_name, "back\\slash;)");
+        assert_eq!(imported.config().variant, Variant::Standard);
+        assert_eq!(imported.transcript(), "f5");
+    }
+
+    #[test]
+    fn multiple_games_and_errors() {
+        let text = "(;GM[Othello]PB[a]PW[b]TY[8]B[f5]W[d6];) (;GM[Othello]PB[c]PW[d]TY[8]B[c4];)";
+        let games = import_games(text).unwrap();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].transcript(), "f5d6");
+        assert_eq!(games[1].black_player_name, "c");
+
+        assert!(import_games("no game here").is_err());
+        assert!(import_games("(;GM[Othello]B[f5]").is_err());
+        assert!(import_games("(;GM[Othello]B[a1];)").is_err());
+        assert!(import_games("(;GM[Chess];)").is_err());
+    }
+}
@@ -84,10 +84,10 @@ impl Logger {
     fn write_log(header: Header, message: String) -> std::io::Result<()> {
         let log_path = Path::new(&*LOG_PATH);
 
-        if let Some(parent_dir) = log_path.parent() {
-            if !parent_dir.exists() {
-                std::fs::create_dir_all(parent_dir)?;
-            }
+        if let Some(parent_dir) = log_path.parent()
+            && !parent_dir.exists()
+        {
+            std::fs::create_dir_all(parent_dir)?;
         }
 
         let log_message = format!(
@@ -111,59 +111,59 @@ impl Logger {
 macro_rules! app_log {
     ($header:expr, $msg:expr, $($arg:tt)*) => {
         let formatted_message = format!($msg, $($arg)*);
-        crate::helpers::logger::LOGGER.log($header, &formatted_message);
+        $crate::helpers::logger::LOGGER.log($header, &formatted_message);
     };
     ($header:expr, $msg:expr) => {
-        crate::helpers::logger::LOGGER.log($header, $msg);
+        $crate::helpers::logger::LOGGER.log($header, $msg);
     };
 }
 
 #[macro_export]
 macro_rules! success_log {
     ($msg:expr, $($arg:tt)*) => {
-        app_log!(crate::helpers::logger::Header::SUCCESS, $msg, $($arg)*);
+        app_log!($crate::helpers::logger::Header::SUCCESS, $msg, $($arg)*);
     };
     ($msg:expr) => {
-        app_log!(crate::helpers::logger::Header::SUCCESS, $msg);
+        app_log!($crate::helpers::logger::Header::SUCCESS, $msg);
     };
 }
 
 #[macro_export]
 macro_rules! info_log {
     ($msg:expr, $($arg:tt)*) => {
-        app_log!(crate::helpers::logger::Header::INFO, $msg, $($arg)*);
+        app_log!($crate::helpers::logger::Header::INFO, $msg, $($arg)*);
     };
     ($msg:expr) => {
-        app_log!(crate::helpers::logger::Header::INFO, $msg);
+        app_log!($crate::helpers::logger::Header::INFO, $msg);
     };
 }
 
 #[macro_export]
 macro_rules! warning_log {
     ($msg:expr, $($arg:tt)*) => {
-        app_log!(crate::helpers::logger::Header::WARNING, $msg, $($arg)*);
+        app_log!($crate::helpers::logger::Header::WARNING, $msg, $($arg)*);
     };
     ($msg:expr) => {
-        app_log!(crate::helpers::logger::Header::WARNING, $msg);
+        app_log!($crate::helpers::logger::Header::WARNING, $msg);
     };
 }
 
 #[macro_export]
 macro_rules! error_log {
     ($msg:expr, $($arg:tt)*) => {
-        app_log!(crate::helpers::logger::Header::ERROR, $msg, $($arg)*);
+        app_log!($crate::helpers::logger::Header::ERROR, $msg, $($arg)*);
     };
     ($msg:expr) => {
-        app_log!(crate::helpers::logger::Header::ERROR, $msg);
+        app_log!($crate::helpers::logger::Header::ERROR, $msg);
     };
 }
 
 #[macro_export]
 macro_rules! debug_log {
     ($msg:expr, $($arg:tt)*) => {
-        app_log!(crate::helpers::logger::Header::DEBUG, $msg, $($arg)*);
+        app_log!($crate::helpers::logger::Header::DEBUG, $msg, $($arg)*);
     };
     ($msg:expr) => {
-        app_log!(crate::helpers::logger::Header::DEBUG, $msg);
+        app_log!($crate::helpers::logger::Header::DEBUG, $msg);
     };
 }
@@ -1,7 +1,23 @@
+pub mod ai;
+pub mod analysis;
+pub mod archive;
+pub mod bitboard;
+pub mod book;
+pub mod clock;
+pub mod error;
 pub mod game;
+pub mod ggf;
 pub mod helpers;
+pub mod mask;
 pub mod matchmaking;
+pub mod mcts;
 pub mod message;
+pub mod notation;
+pub mod opening;
 pub mod presentation;
+pub mod rating;
 pub mod server;
 pub mod session;
+pub mod solver;
+pub mod wthor;
+pub mod zobrist;
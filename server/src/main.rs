@@ -1,6 +1,7 @@
 use actix::prelude::*;
 use actix_cors::Cors;
 use actix_web::{App, HttpResponse, HttpServer, web};
+use std::env;
 
 use server::helpers::logger;
 use server::presentation::routes::ws_route::ws_index;
@@ -10,7 +11,10 @@ use server::server::GameServer;
 async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
-    let game_server = GameServer::new().start();
+    // 終了した対局を自動で解析する場合は`REVERSI_AUTO_ANALYSIS=1`を指定する
+    let auto_analysis =
+        env::var("REVERSI_AUTO_ANALYSIS").is_ok_and(|value| matches!(value.as_str(), "1" | "true"));
+    let game_server = GameServer::new().with_auto_analysis(auto_analysis).start();
 
     logger::LOGGER.log(logger::Header::INFO, "Initializing reversi...");
 
@@ -0,0 +1,84 @@
+//! # 盤面マスク
+//!
+//! 一部のマスを穴（石を置けないマス）にした盤面のテンプレート
+//!
+//! 穴は盤の端と同じく扱われ、石を置くことも挟むこともできない
+//!
+//! ## 関数
+//!
+//! - `holes`: テンプレートから穴にするマスを決める
+//! - `apply`: 盤面に穴を開ける
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::bitboard::Board;
+
+/// 盤面マスクのテンプレート
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoardMask {
+    /// 穴なし
+    #[default]
+    Full,
+    /// 四隅を穴にする
+    NoCorners,
+    /// 四隅の`size / 4`四方を穴にした十字形
+    Cross,
+    /// 無作為に選んだマスを点対称な組で穴にする
+    Random,
+}
+
+impl fmt::Display for BoardMask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardMask::Full => write!(f, "full"),
+            BoardMask::NoCorners => write!(f, "no_corners"),
+            BoardMask::Cross => write!(f, "cross"),
+            BoardMask::Random => write!(f, "random"),
+        }
+    }
+}
+
+/// テンプレートから穴にするマスを決める
+pub fn holes<R: Rng>(mask: BoardMask, size: usize, rng: &mut R) -> Vec<(usize, usize)> {
+    let last = size - 1;
+    match mask {
+        BoardMask::Full => Vec::new(),
+        BoardMask::NoCorners => vec![(0, 0), (last, 0), (0, last), (last, last)],
+        BoardMask::Cross => {
+            let arm = size / 4;
+            (0..size)
+                .flat_map(|y| (0..size).map(move |x| (x, y)))
+                .filter(|&(x, y)| (x < arm || x > last - arm) && (y < arm || y > last - arm))
+                .collect()
+        }
+        BoardMask::Random => {
+            // 初期配置の周囲（中央の4x4）は開始直後の着手のために残す
+            let center = size / 2 - 2..=size / 2 + 1;
+            let candidates: Vec<(usize, usize)> = (0..size)
+                .flat_map(|y| (0..size).map(move |x| (x, y)))
+                .filter(|&(x, y)| !(center.contains(&x) && center.contains(&y)))
+                .collect();
+
+            let mut holes = Vec::new();
+            while holes.len() < size / 4 * 2 {
+                let (x, y) = candidates[rng.gen_range(0..candidates.len())];
+                let mirrored = (last - x, last - y);
+                if !holes.contains(&(x, y)) {
+                    holes.push((x, y));
+                    holes.push(mirrored);
+                }
+            }
+            holes
+        }
+    }
+}
+
+/// 盤面に穴を開ける
+pub fn apply<R: Rng>(board: &mut Board, mask: BoardMask, rng: &mut R) {
+    for (x, y) in holes(mask, board.size(), rng) {
+        board.set_hole(x, y);
+    }
+}
@@ -2,6 +2,7 @@ use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
 use uuid::Uuid;
 
+use crate::game::{GameConfig, Handicap};
 use crate::{app_log, debug_log, info_log, warning_log};
 
 /// マッチングキューのエントリー
@@ -13,6 +14,8 @@ pub struct QueueEntry {
     pub username: String,
     /// レーティング
     pub rating: u32,
+    /// 希望するゲーム設定
+    pub config: GameConfig,
     /// キューに入った時刻
     pub joined_at: Instant,
 }
@@ -30,6 +33,10 @@ pub struct PendingMatch {
     pub player2_id: String,
     /// プレイヤー2の名前
     pub player2_name: String,
+    /// ゲーム設定
+    pub config: GameConfig,
+    /// レーティング差に応じた置き石（プレイヤー1が黒、プレイヤー2が白）
+    pub handicap: Option<Handicap>,
     /// マッチが作成された時刻
     pub created_at: Instant,
     /// プレイヤー1の準備完了フラグ
@@ -48,6 +55,12 @@ pub struct MatchmakingService {
     pending_matches: HashMap<String, PendingMatch>,
 }
 
+impl Default for MatchmakingService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MatchmakingService {
     pub fn new() -> Self {
         Self {
@@ -58,7 +71,13 @@ impl MatchmakingService {
     }
 
     /// マッチングキューにプレイヤーを追加
-    pub fn add_to_queue(&mut self, user_id: String, username: String, rating: u32) -> bool {
+    pub fn add_to_queue(
+        &mut self,
+        user_id: String,
+        username: String,
+        rating: u32,
+        config: GameConfig,
+    ) -> bool {
         // 既にキューにいる場合は追加しない
         if self.queue.contains_key(&user_id) {
             return false;
@@ -69,6 +88,7 @@ impl MatchmakingService {
             user_id: user_id.clone(),
             username,
             rating,
+            config,
             joined_at: Instant::now(),
         };
 
@@ -98,48 +118,60 @@ impl MatchmakingService {
     }
 
     /// マッチを探す
-    /// FIFOマッチメイキングだが、使用感によっては変更したい
+    /// 同じゲーム設定を希望するプレイヤー同士をFIFOでマッチングし、
+    /// レーティング差が大きい場合は弱い側に置き石を与える
     pub fn find_matches(&mut self) -> Vec<PendingMatch> {
         let mut created_matches = Vec::new();
+        // 相手が見つかっていないプレイヤー（キューに入った順）
+        let mut waiting: VecDeque<String> = VecDeque::new();
 
-        // キュー内のプレイヤーが2人以上いる場合、マッチングを試みる
-        while self.queue.len() >= 2 {
-            // キューから最も古い2つのエントリを取得
-            if let Some(player1_id) = self.queue_order.pop_front() {
-                if let Some(player2_id) = self.queue_order.pop_front() {
-                    // プレイヤー情報を取得
-                    let player1 = self.queue.remove(&player1_id).unwrap();
-                    let player2 = self.queue.remove(&player2_id).unwrap();
-
-                    // マッチを作成
-                    let match_id = Uuid::new_v4().to_string();
-                    let new_match = PendingMatch {
-                        match_id: match_id.clone(),
-                        player1_id: player1.user_id,
-                        player1_name: player1.username,
-                        player2_id: player2.user_id,
-                        player2_name: player2.username,
-                        created_at: Instant::now(),
-                        player1_ready: false,
-                        player2_ready: false,
-                    };
-
-                    // 保留中のマッチに参加
-                    self.pending_matches
-                        .insert(match_id.clone(), new_match.clone());
-                    created_matches.push(new_match);
-
-                    info_log!("New match created: {}", match_id);
-                } else {
-                    // Player2がいない場合はPlayer1をキューに戻す
-                    self.queue_order.push_front(player1_id);
-                    break;
-                }
-            } else {
-                break;
-            }
+        while let Some(player2_id) = self.queue_order.pop_front() {
+            // 同じ設定で待っている最も古いプレイヤーを探す
+            let config = &self.queue[&player2_id].config;
+            let opponent = waiting
+                .iter()
+                .position(|id| &self.queue[id].config == config);
+
+            let Some(position) = opponent else {
+                waiting.push_back(player2_id);
+                continue;
+            };
+
+            // プレイヤー情報を取得
+            let player1_id = waiting.remove(position).unwrap();
+            let player1 = self.queue.remove(&player1_id).unwrap();
+            let player2 = self.queue.remove(&player2_id).unwrap();
+
+            // 設定と組み合わせられる場合のみ置き石を与える
+            let handicap = Handicap::from_ratings(player1.rating, player2.rating)
+                .filter(|handicap| handicap.validate(&player1.config).is_ok());
+
+            // マッチを作成
+            let match_id = Uuid::new_v4().to_string();
+            let new_match = PendingMatch {
+                match_id: match_id.clone(),
+                player1_id: player1.user_id,
+                player1_name: player1.username,
+                player2_id: player2.user_id,
+                player2_name: player2.username,
+                config: player1.config,
+                handicap,
+                created_at: Instant::now(),
+                player1_ready: false,
+                player2_ready: false,
+            };
+
+            // 保留中のマッチに参加
+            self.pending_matches
+                .insert(match_id.clone(), new_match.clone());
+            created_matches.push(new_match);
+
+            info_log!("New match created: {}", match_id);
         }
 
+        // マッチしなかったプレイヤーを順番を保ったままキューに戻す
+        self.queue_order = waiting;
+
         created_matches
     }
 
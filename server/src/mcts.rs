@@ -0,0 +1,639 @@
+//! # モンテカルロ木探索
+//!
+//! 評価関数を使わず、終局までの試行（プレイアウト）の勝率で手を選ぶプレイヤー（UCT）
+//!
+//! 勝敗は`Variant`の規則だけから決まるため、手作りの評価関数が当てはまらない
+//! アンチリバーシや穴あき盤面、8x8以外の盤面でもそのまま使える
+//!
+//! - 選択: 勝率にUCB1の探索項を加えた値が最大の子を選ぶ
+//! - 展開: まだ試していない合法手を1つ無作為に選んで木に加える
+//! - プレイアウト: `Playout`の方針で終局まで打ち、勝敗を経路上の節点に記録する
+//! - 木の再利用: 前回の思考の後に`Game`で打たれた手を辿り、その部分木から続きを探索する
+//!
+//! ## 関数
+//!
+//! - `Mcts::new`:         設定からプレイヤーを作成
+//! - `Mcts::choose_move`: ゲームの現在の局面で手を選ぶ
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::bitboard::{Bitboard, Bits, Board, Geometry, WideBits};
+use crate::game::{DiskColor, Game, MoveAction, Variant};
+
+/// 既定のプレイアウト回数
+const DEFAULT_PLAYOUTS: u32 = 10_000;
+/// 既定の探索項の係数（√2）
+const DEFAULT_EXPLORATION: f64 = std::f64::consts::SQRT_2;
+/// 持ち時間を確認する間隔（プレイアウト回数）
+const DEADLINE_CHECK_INTERVAL: u32 = 64;
+
+/// プレイアウトで手を選ぶ方針
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Playout {
+    /// 合法手から無作為に選ぶ
+    #[default]
+    Random,
+    /// 隅があれば取り、隅の斜め隣（Xマス）はほかに手がない場合だけ打つ（通常ルール向け）
+    Light,
+}
+
+/// 探索の設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MctsConfig {
+    /// 1手あたりのプレイアウト回数
+    pub playouts: u32,
+    /// UCB1の探索項の係数（大きいほど試行の少ない手を読む）
+    pub exploration: f64,
+    /// プレイアウトの方針
+    pub playout: Playout,
+}
+
+impl Default for MctsConfig {
+    fn default() -> Self {
+        Self {
+            playouts: DEFAULT_PLAYOUTS,
+            exploration: DEFAULT_EXPLORATION,
+            playout: Playout::default(),
+        }
+    }
+}
+
+/// 探索木の節点
+struct Node<B> {
+    /// 手番側の石
+    player: B,
+    /// 相手の石
+    opponent: B,
+    /// 手番側の色
+    color: DiskColor,
+    /// この節点に至った手（パスの場合はNone）
+    square: Option<usize>,
+    /// まだ木に加えていない合法手
+    untried: B,
+    /// 子節点の位置
+    children: Vec<usize>,
+    /// 両者とも打てず終局しているか
+    terminal: bool,
+    visits: u32,
+    /// この節点に至る手を打った側から見た得点の合計（勝ち1、引き分け0.5）
+    reward: f64,
+}
+
+impl<B> Node<B> {
+    /// UCB1の値（親の訪問回数の対数`log_visits`を渡す）
+    fn ucb(&self, log_visits: f64, exploration: f64) -> f64 {
+        let visits = self.visits as f64;
+        self.reward / visits + exploration * (log_visits / visits).sqrt()
+    }
+}
+
+/// 1つのゲームの探索木
+struct Tree<B: Bits> {
+    geometry: &'static Geometry<B>,
+    holes: B,
+    size: usize,
+    variant: Variant,
+    /// 隅のマス
+    corners: B,
+    /// 隅の斜め隣のマス
+    x_squares: B,
+    nodes: Vec<Node<B>>,
+    /// 根の局面までの手数（`Game::move_history`の長さ）
+    ply: usize,
+}
+
+impl<B: Bits> Tree<B> {
+    fn new(board: &Bitboard<B>, color: DiskColor, variant: Variant, ply: usize) -> Self {
+        let size = board.size();
+        let last = size - 1;
+        let corners = [(0, 0), (last, 0), (0, last), (last, last)]
+            .into_iter()
+            .fold(B::ZERO, |acc, (x, y)| acc | B::bit(board.index(x, y)));
+        let x_squares = [(1, 1), (last - 1, 1), (1, last - 1), (last - 1, last - 1)]
+            .into_iter()
+            .fold(B::ZERO, |acc, (x, y)| acc | B::bit(board.index(x, y)));
+
+        let mut tree = Self {
+            geometry: board.geometry(),
+            holes: board.holes(),
+            size,
+            variant,
+            corners,
+            x_squares,
+            nodes: Vec::new(),
+            ply,
+        };
+        tree.add_node(
+            board.discs(color),
+            board.discs(color.opposite()),
+            color,
+            None,
+        );
+        tree
+    }
+
+    #[inline]
+    fn legal_moves(&self, player: B, opponent: B) -> B {
+        self.geometry.legal_moves(player, opponent, self.holes)
+    }
+
+    #[inline]
+    fn play(&self, player: B, opponent: B, index: usize) -> (B, B) {
+        let bit = B::bit(index);
+        let flipped = self.geometry.flips(player, opponent, bit);
+        (player | bit | flipped, opponent & !flipped)
+    }
+
+    /// 節点を追加して位置を返す（パスしかできない局面ではパスの子も追加する）
+    fn add_node(
+        &mut self,
+        player: B,
+        opponent: B,
+        color: DiskColor,
+        square: Option<usize>,
+    ) -> usize {
+        let moves = self.legal_moves(player, opponent);
+        let must_pass = moves.is_zero() && !self.legal_moves(opponent, player).is_zero();
+        let index = self.nodes.len();
+        self.nodes.push(Node {
+            player,
+            opponent,
+            color,
+            square,
+            untried: moves,
+            children: Vec::new(),
+            terminal: moves.is_zero() && !must_pass,
+            visits: 0,
+            reward: 0.0,
+        });
+
+        if must_pass {
+            let child = self.add_node(opponent, player, color.opposite(), None);
+            self.nodes[index].children.push(child);
+        }
+        index
+    }
+
+    /// 根から1回分の選択・展開・プレイアウト・逆伝播を行う
+    fn iterate<R: Rng>(&mut self, config: &MctsConfig, rng: &mut R) {
+        let mut path = vec![0];
+        let mut current = 0;
+
+        loop {
+            let node = &self.nodes[current];
+            if node.terminal {
+                break;
+            }
+
+            // 試していない手があれば1つ展開する
+            if !node.untried.is_zero() {
+                let (player, opponent, color) = (node.player, node.opponent, node.color);
+                let square = random_square(node.untried, rng);
+                self.nodes[current].untried = node.untried & !B::bit(square);
+
+                let (next_player, next_opponent) = self.play(player, opponent, square);
+                let child =
+                    self.add_node(next_opponent, next_player, color.opposite(), Some(square));
+                self.nodes[current].children.push(child);
+                path.push(child);
+                current = child;
+                break;
+            }
+
+            let log_visits = (node.visits.max(1) as f64).ln();
+            current = *node
+                .children
+                .iter()
+                .max_by(|&&a, &&b| {
+                    let a = self.nodes[a].ucb(log_visits, config.exploration);
+                    let b = self.nodes[b].ucb(log_visits, config.exploration);
+                    a.total_cmp(&b)
+                })
+                .expect("Non-terminal node without moves has no children");
+            path.push(current);
+        }
+
+        let node = &self.nodes[current];
+        let winner = self.playout(node.player, node.opponent, node.color, config.playout, rng);
+
+        for index in path {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            node.reward += match winner {
+                Some(color) if color == node.color.opposite() => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+        }
+    }
+
+    /// 終局まで打ち進めて勝者の色を返す（引き分けはNone）
+    fn playout<R: Rng>(
+        &self,
+        mut player: B,
+        mut opponent: B,
+        mut color: DiskColor,
+        policy: Playout,
+        rng: &mut R,
+    ) -> Option<DiskColor> {
+        let mut passed = false;
+
+        loop {
+            let moves = self.legal_moves(player, opponent);
+            if moves.is_zero() {
+                if passed {
+                    break;
+                }
+                passed = true;
+            } else {
+                passed = false;
+                let square = match policy {
+                    Playout::Random => random_square(moves, rng),
+                    Playout::Light => self.light_square(moves, rng),
+                };
+                (player, opponent) = self.play(player, opponent, square);
+            }
+            (player, opponent) = (opponent, player);
+            color = color.opposite();
+        }
+
+        let (black, white) = match color {
+            DiskColor::Black => (player, opponent),
+            DiskColor::White => (opponent, player),
+        };
+        self.variant
+            .winner(black.count() as usize, white.count() as usize)
+    }
+
+    /// 隅を優先し、Xマスを避けて選んだ手
+    fn light_square<R: Rng>(&self, moves: B, rng: &mut R) -> usize {
+        let corners = moves & self.corners;
+        if !corners.is_zero() {
+            return random_square(corners, rng);
+        }
+        let safe = moves & !self.x_squares;
+        random_square(if safe.is_zero() { moves } else { safe }, rng)
+    }
+
+    /// 根から`history`の手を辿った節点（木に含まれていない場合はNone）
+    fn descend(&self, history: &[MoveAction]) -> Option<usize> {
+        let mut current = 0;
+        for action in history {
+            let square = match *action {
+                MoveAction::Place { x, y } => Some(y * self.size + x),
+                MoveAction::Pass => None,
+            };
+            current = *self.nodes[current]
+                .children
+                .iter()
+                .find(|&&child| self.nodes[child].square == square)?;
+        }
+        Some(current)
+    }
+
+    /// `root`以下の部分木だけを残して新しい根にする
+    fn reroot(&mut self, root: usize, ply: usize) {
+        let mut old = std::mem::take(&mut self.nodes);
+        let mut queue = vec![root];
+        let mut position = 0;
+
+        // 幅優先で並べ直し、子の位置を新しい位置に付け替える
+        while position < queue.len() {
+            let children = std::mem::take(&mut old[queue[position]].children);
+            let first = queue.len();
+            queue.extend(&children);
+            old[queue[position]].children = (first..queue.len()).collect();
+            position += 1;
+        }
+
+        let mut slots: Vec<Option<Node<B>>> = old.into_iter().map(Some).collect();
+        self.nodes = queue
+            .into_iter()
+            .map(|index| slots[index].take().expect("Node visited twice"))
+            .collect();
+        self.ply = ply;
+    }
+
+    /// 根の局面が`board`の`color`の手番と一致するか
+    fn matches(&self, board: &Bitboard<B>, color: DiskColor) -> bool {
+        let root = &self.nodes[0];
+        root.color == color
+            && root.player == board.discs(color)
+            && root.opponent == board.discs(color.opposite())
+    }
+
+    /// 最も多く試行した根の子の手
+    fn best_square(&self) -> Option<usize> {
+        self.nodes[0]
+            .children
+            .iter()
+            .map(|&child| &self.nodes[child])
+            .filter(|node| node.square.is_some())
+            .max_by_key(|node| node.visits)
+            .and_then(|node| node.square)
+    }
+}
+
+/// 盤面サイズごとの探索木
+enum Trees {
+    Narrow(Tree<u64>),
+    Wide(Tree<WideBits>),
+}
+
+/// `moves`から無作為に選んだマス
+fn random_square<B: Bits, R: Rng>(moves: B, rng: &mut R) -> usize {
+    let nth = rng.gen_range(0..moves.count() as usize);
+    moves.indices().nth(nth).expect("Random index out of range")
+}
+
+/// 根を`game`の現在の局面に合わせた探索木（引き継げない場合は作り直す）
+fn prepare<B: Bits>(
+    tree: Option<Tree<B>>,
+    board: &Bitboard<B>,
+    game: &Game,
+    variant: Variant,
+) -> Tree<B> {
+    let history = game.move_history();
+    let color = game.current_color();
+
+    if let Some(mut tree) = tree
+        && tree.variant == variant
+        && tree.ply <= history.len()
+    {
+        let actions: Vec<MoveAction> = history[tree.ply..].iter().map(|m| m.action).collect();
+        if let Some(root) = tree.descend(&actions) {
+            tree.reroot(root, history.len());
+            if tree.matches(board, color) {
+                return tree;
+            }
+        }
+    }
+
+    Tree::new(board, color, variant, history.len())
+}
+
+/// 設定された回数か持ち時間までプレイアウトを行う
+///
+/// 持ち時間が残っていなくても手を選べるよう、最初の確認まではプレイアウトを行う
+fn run<B: Bits, R: Rng>(
+    tree: &mut Tree<B>,
+    config: &MctsConfig,
+    deadline: Option<Instant>,
+    rng: &mut R,
+) {
+    for playout in 0..config.playouts {
+        if playout > 0
+            && playout.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+            && deadline.is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            break;
+        }
+        tree.iterate(config, rng);
+    }
+}
+
+/// モンテカルロ木探索のプレイヤー
+///
+/// 同じゲームで続けて`choose_move`を呼ぶと、前回の探索木のうち実際に打たれた手の先を引き継ぐ
+pub struct Mcts {
+    config: MctsConfig,
+    tree: Option<Trees>,
+}
+
+impl Mcts {
+    pub fn new(config: MctsConfig) -> Self {
+        Self { config, tree: None }
+    }
+
+    /// 探索の設定
+    pub fn config(&self) -> &MctsConfig {
+        &self.config
+    }
+
+    /// `game`の手番側の手を選ぶ（終局している場合や打てる手がない場合はNone）
+    ///
+    /// `time_limit`を指定すると、プレイアウト回数に達する前でも時間になれば打ち切る
+    pub fn choose_move<R: Rng>(
+        &mut self,
+        game: &Game,
+        time_limit: Option<Duration>,
+        rng: &mut R,
+    ) -> Option<(usize, usize)> {
+        if game.is_over() {
+            return None;
+        }
+        let deadline = time_limit.map(|limit| Instant::now() + limit);
+        let variant = game.config().variant;
+
+        let (trees, square) = match (game.board(), self.tree.take()) {
+            (Board::Narrow(board), tree) => {
+                let tree = match tree {
+                    Some(Trees::Narrow(tree)) => Some(tree),
+                    _ => None,
+                };
+                let mut tree = prepare(tree, board, game, variant);
+                run(&mut tree, &self.config, deadline, rng);
+                let square = tree.best_square().map(|index| board.coordinates(index));
+                (Trees::Narrow(tree), square)
+            }
+            (Board::Wide(board), tree) => {
+                let tree = match tree {
+                    Some(Trees::Wide(tree)) => Some(tree),
+                    _ => None,
+                };
+                let mut tree = prepare(tree, board, game, variant);
+                run(&mut tree, &self.config, deadline, rng);
+                let square = tree.best_square().map(|index| board.coordinates(index));
+                (Trees::Wide(tree), square)
+            }
+        };
+
+        self.tree = Some(trees);
+        square
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::GameConfig;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn config(playouts: u32) -> MctsConfig {
+        MctsConfig {
+            playouts,
+            ..MctsConfig::default()
+        }
+    }
+
+    fn new_game() -> Game {
+        Game::new(
+            "mcts".to_string(),
+            "black-id".to_string(),
+            "black".to_string(),
+            "white-id".to_string(),
+            "white".to_string(),
+            GameConfig::default(),
+            None,
+        )
+    }
+
+    /// 1行目と8行目が`-OXXXXXX`で中央が黒の局面（黒がa1に打つと白はパスする）
+    fn pass_game(side: DiskColor) -> Game {
+        let (edge, empty, center) = ("-OXXXXXX", "-".repeat(16), "---XX---".repeat(2));
+        let text = format!("{}{}{}{}{}", edge, empty, center, empty, edge);
+        Game::from_position(
+            "mcts".to_string(),
+            "black-id".to_string(),
+            "black".to_string(),
+            "white-id".to_string(),
+            "white".to_string(),
+            &text,
+            side,
+        )
+        .unwrap()
+    }
+
+    fn narrow(game: &Game) -> Bitboard<u64> {
+        match game.board() {
+            Board::Narrow(board) => *board,
+            Board::Wide(_) => panic!("Expected an 8x8 board"),
+        }
+    }
+
+    fn play(game: &mut Game, notation: &str) {
+        let player_id = game.player_id(game.current_color()).to_string();
+        game.make_move_notation(&player_id, notation).unwrap();
+    }
+
+    /// 根から辿れる節点の数
+    fn reachable<B: Bits>(tree: &Tree<B>) -> usize {
+        let mut stack = vec![0];
+        let mut count = 0;
+        while let Some(index) = stack.pop() {
+            count += 1;
+            stack.extend(&tree.nodes[index].children);
+        }
+        count
+    }
+
+    #[test]
+    fn reuses_the_subtree_after_both_sides_move() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut game = new_game();
+        let mut tree = prepare(None, &narrow(&game), &game, Variant::Standard);
+        run(&mut tree, &config(2000), None, &mut rng);
+
+        play(&mut game, "f5");
+        play(&mut game, "d6");
+        let actions: Vec<MoveAction> = game.move_history().iter().map(|m| m.action).collect();
+        let root = tree.descend(&actions).expect("Moves were not explored");
+        let visits = tree.nodes[root].visits;
+        assert!(visits > 0);
+
+        let tree = prepare(Some(tree), &narrow(&game), &game, Variant::Standard);
+        assert_eq!(tree.ply, 2);
+        assert_eq!(tree.nodes[0].visits, visits);
+        assert!(tree.matches(&narrow(&game), DiskColor::Black));
+        // 並べ直した後も全ての節点が根から辿れる
+        assert_eq!(reachable(&tree), tree.nodes.len());
+    }
+
+    #[test]
+    fn follows_an_automatic_pass() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut game = pass_game(DiskColor::Black);
+        let mut tree = prepare(None, &narrow(&game), &game, Variant::Standard);
+        run(&mut tree, &config(200), None, &mut rng);
+
+        play(&mut game, "a1");
+        let actions: Vec<MoveAction> = game.move_history().iter().map(|m| m.action).collect();
+        assert_eq!(actions[1], MoveAction::Pass);
+
+        let tree = prepare(Some(tree), &narrow(&game), &game, Variant::Standard);
+        assert_eq!(tree.ply, 2);
+        assert_eq!(tree.nodes[0].square, None);
+        assert_eq!(tree.nodes[0].color, DiskColor::Black);
+        assert!(tree.nodes[0].visits > 0);
+        assert!(tree.matches(&narrow(&game), DiskColor::Black));
+        assert_eq!(
+            tree.best_square()
+                .map(|index| narrow(&game).coordinates(index)),
+            Some((0, 7))
+        );
+    }
+
+    #[test]
+    fn discards_a_tree_for_another_position() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let game = new_game();
+        let mut tree = prepare(None, &narrow(&game), &game, Variant::Standard);
+        run(&mut tree, &config(100), None, &mut rng);
+
+        // 手数は同じでも局面が異なる
+        let other = pass_game(DiskColor::Black);
+        let board = narrow(&other);
+        assert!(tree.descend(&[]).is_some());
+        assert!(!tree.matches(&board, DiskColor::Black));
+
+        let tree = prepare(Some(tree), &board, &other, Variant::Standard);
+        assert_eq!(tree.nodes.len(), 1);
+        assert_eq!(tree.nodes[0].visits, 0);
+        assert!(tree.matches(&board, DiskColor::Black));
+
+        // 規則が異なる場合も作り直す
+        let mut tree = prepare(None, &board, &other, Variant::Standard);
+        run(&mut tree, &config(100), None, &mut rng);
+        let tree = prepare(Some(tree), &board, &other, Variant::AntiReversi);
+        assert_eq!(tree.nodes[0].visits, 0);
+        assert_eq!(tree.variant, Variant::AntiReversi);
+    }
+
+    #[test]
+    fn best_square_is_never_a_pass() {
+        let mut rng = StdRng::seed_from_u64(4);
+
+        // 白は打てないため根の子はパスだけになる
+        let game = pass_game(DiskColor::White);
+        let board = narrow(&game);
+        let mut tree = Tree::new(&board, DiskColor::White, Variant::Standard, 0);
+        run(&mut tree, &config(100), None, &mut rng);
+        assert_eq!(tree.nodes[0].children.len(), 1);
+        assert!(tree.nodes[0].visits > 0);
+        assert_eq!(tree.best_square(), None);
+
+        let game = new_game();
+        let board = narrow(&game);
+        let mut tree = Tree::new(&board, DiskColor::Black, Variant::Standard, 0);
+        run(&mut tree, &config(100), None, &mut rng);
+        let square = tree.best_square().map(|index| board.coordinates(index));
+        assert!(game.legal_moves().contains(&square.unwrap()));
+    }
+
+    #[test]
+    fn respects_the_playout_budget() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let game = new_game();
+        let board = narrow(&game);
+
+        let mut tree = Tree::new(&board, DiskColor::Black, Variant::Standard, 0);
+        run(&mut tree, &config(150), None, &mut rng);
+        assert_eq!(tree.nodes[0].visits, 150);
+
+        // 持ち時間が過ぎていても最初の確認まではプレイアウトを行う
+        let mut tree = Tree::new(&board, DiskColor::Black, Variant::Standard, 0);
+        run(&mut tree, &config(10_000), Some(Instant::now()), &mut rng);
+        assert_eq!(tree.nodes[0].visits, DEADLINE_CHECK_INTERVAL);
+
+        let mut mcts = Mcts::new(config(150));
+        assert!(mcts.choose_move(&game, None, &mut rng).is_some());
+        match &mcts.tree {
+            Some(Trees::Narrow(tree)) => assert_eq!(tree.nodes[0].visits, 150),
+            _ => panic!("Expected an 8x8 tree"),
+        }
+    }
+}
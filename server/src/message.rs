@@ -1,6 +1,17 @@
 use actix::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::ai::{Difficulty, Engine, RankedMove};
+use crate::analysis::AnalysisReport;
+use crate::bitboard::DEFAULT_BOARD_SIZE;
+use crate::book::BookMove;
+use crate::clock::{ClockState, TimeControl};
+use crate::error::GameError;
+use crate::game::{
+    DiskColor, Game, GameConfig, GameResult, GameState, Handicap, HintRecord, Variant,
+};
+use crate::mask::BoardMask;
+use crate::opening::StartPosition;
 use crate::session::WsGameSession;
 
 /// クライアントからのメッセージを表す列挙型
@@ -13,7 +24,26 @@ pub enum ClientMessage {
 
     /// マッチングキューに参加
     #[serde(rename = "join_queue")]
-    JoinQueue,
+    JoinQueue {
+        #[serde(flatten)]
+        options: GameOptions,
+    },
+
+    /// コンピューターとの対局を開始
+    #[serde(rename = "play_computer")]
+    PlayComputer {
+        /// 手を選ぶ方式（省略時はαβ法）
+        #[serde(default)]
+        engine: Option<Engine>,
+        /// コンピューターの強さ（省略時は普通）
+        #[serde(default)]
+        difficulty: Option<Difficulty>,
+        /// 自分の石の色（省略時は無作為）
+        #[serde(default)]
+        color: Option<DiskColor>,
+        #[serde(flatten)]
+        options: GameOptions,
+    },
 
     /// マッチングキューから離脱
     #[serde(rename = "leave_queue")]
@@ -21,17 +51,114 @@ pub enum ClientMessage {
 
     /// ゲームの手を打つ
     #[serde(rename = "make_move")]
-    MakeMove { x: usize, y: usize },
+    MakeMove(MoveInput),
 
     /// ゲームを降参する
     #[serde(rename = "resign")]
     Resign,
 
+    /// 自分が参照できる過去のゲーム一覧を要求
+    #[serde(rename = "list_games")]
+    ListGames,
+
+    /// 終了したゲームをGGF形式で書き出す
+    #[serde(rename = "export_game")]
+    ExportGame { game_id: String },
+
+    /// GGF形式の棋譜を読み込む（複数ゲーム可）
+    #[serde(rename = "import_game")]
+    ImportGame { ggf: String },
+
+    /// 局面の定石手と統計を要求（盤面を省略すると対局中の現在の局面）
+    #[serde(rename = "explore_opening")]
+    ExploreOpening {
+        /// 盤面文字列（`X`=黒、`O`=白、`-`=空き、`#`=穴）
+        #[serde(default)]
+        board: Option<String>,
+        /// 手番（省略時は黒）
+        #[serde(default)]
+        side: Option<DiskColor>,
+        /// ルールの種類（省略時は通常ルール）
+        #[serde(default)]
+        variant: Option<Variant>,
+    },
+
+    /// 局面の合法手の評価を要求
+    ///
+    /// 盤面を省略すると対局中の現在の局面（ヒント、カジュアル対局のみで回数制限あり）、
+    /// 指定すると任意の局面の検討（回数制限なし、レーティング対局の最中は使えない）
+    #[serde(rename = "request_hint")]
+    RequestHint {
+        /// 盤面文字列（`X`=黒、`O`=白、`-`=空き、`#`=穴）
+        #[serde(default)]
+        board: Option<String>,
+        /// 手番（省略時は黒）
+        #[serde(default)]
+        side: Option<DiskColor>,
+        /// ルールの種類（省略時は通常ルール）
+        #[serde(default)]
+        variant: Option<Variant>,
+    },
+
+    /// 過去のゲームの解析結果を要求
+    #[serde(rename = "request_analysis")]
+    RequestAnalysis { game_id: String },
+
     /// ハートビート
     #[serde(rename = "heartbeat")]
     Heartbeat,
 }
 
+/// 対局を始めるときに指定するゲーム設定（省略した項目は既定値）
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GameOptions {
+    /// 盤面の一辺の長さ（省略時は8）
+    #[serde(default)]
+    pub board_size: Option<usize>,
+    /// ルールの種類（省略時は通常ルール）
+    #[serde(default)]
+    pub variant: Option<Variant>,
+    /// 開始局面の種類（省略時は通常の初期配置）
+    #[serde(default)]
+    pub start: Option<StartPosition>,
+    /// 穴を開けるマスのテンプレート（省略時は穴なし）
+    #[serde(default)]
+    pub mask: Option<BoardMask>,
+    /// 持ち時間の方式（省略時は時間制限なし）
+    #[serde(default)]
+    pub time_control: Option<TimeControl>,
+    /// レーティングに反映しない対局にするか（省略時は反映する）
+    #[serde(default)]
+    pub casual: Option<bool>,
+}
+
+impl GameOptions {
+    /// 省略した項目を既定値にしたゲーム設定
+    pub fn into_config(self) -> GameConfig {
+        GameConfig {
+            board_size: self.board_size.unwrap_or(DEFAULT_BOARD_SIZE),
+            variant: self.variant.unwrap_or_default(),
+            start: self.start.unwrap_or_default(),
+            mask: self.mask.unwrap_or_default(),
+            time_control: self.time_control.unwrap_or_default(),
+            casual: self.casual.unwrap_or_default(),
+        }
+    }
+}
+
+/// 打つ手の指定方法
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MoveInput {
+    /// 座標（例: `{"x": 5, "y": 4}`）
+    Coordinates { x: usize, y: usize },
+    /// 標準座標表記（例: `{"move": "f5"}`）
+    Notation {
+        #[serde(rename = "move")]
+        notation: String,
+    },
+}
+
 /// サーバーからクライアントへのメッセージを表す列挙型
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -42,26 +169,198 @@ pub enum ServerMessage {
 
     /// エラーメッセージ
     #[serde(rename = "error")]
-    Error { message: String },
+    Error {
+        message: String,
+        /// 機械可読なエラーコード（ゲームのエラーの場合、例: `not_your_turn`）
+        code: Option<String>,
+    },
 
     /// マッチが見つかった通知
     #[serde(rename = "match_found")]
-    MatchFound { opponent: String },
+    MatchFound {
+        game_id: String,
+        opponent: String,
+        variant: Variant,
+        /// 置き石（ハンディキャップなしの場合はNone）
+        handicap: Option<Handicap>,
+    },
 
     /// ゲーム状態の更新
     #[serde(rename = "game_state")]
     GameState {
         board: Vec<Vec<Option<String>>>,
+        board_size: usize,
+        variant: Variant,
         current_player: String,
         your_color: String,
+        /// 手番側が打てるマスの一覧（`[x, y]`）
+        legal_moves: Vec<[usize; 2]>,
+        /// 石を置けない穴のマスの一覧（`[x, y]`）
+        holes: Vec<[usize; 2]>,
+        black_can_move: bool,
+        white_can_move: bool,
+        /// 最後に石が置かれたマス（`[x, y]`）
+        last_move: Option<[usize; 2]>,
+        /// 最後の手で反転した石の一覧（`[x, y]`）
+        flipped: Vec<[usize; 2]>,
+        black_count: usize,
+        white_count: usize,
+        /// 局面のZobristキー（16進数）
+        position_key: String,
+        /// 対称な局面で共通となるZobristキー（16進数）
+        canonical_key: String,
+        /// 両プレイヤーの残り時間（時間制限なしの場合はNone）
+        clock: Option<ClockState>,
     },
 
+    /// パス通知（打てる場所がなく手番が飛ばされた）
+    #[serde(rename = "pass")]
+    Pass { color: String, player: String },
+
     /// ゲーム終了通知
     #[serde(rename = "game_over")]
     GameOver {
         winner: Option<String>,
+        /// 終局の理由の説明
         reason: String,
+        /// 終局の理由、勝者の色、最終スコア
+        result: GameResult,
+        /// 棋譜文字列（例: `f5d6c3`）
+        transcript: String,
+        /// 開始局面までの手順（XOTやランダム開始の場合）
+        opening: Option<String>,
+        /// 置き石（ハンディキャップなしの場合はNone）
+        handicap: Option<Handicap>,
+    },
+
+    /// 過去のゲーム一覧
+    #[serde(rename = "game_list")]
+    GameList { games: Vec<GameSummary> },
+
+    /// GGF形式の棋譜
+    #[serde(rename = "game_record")]
+    GameRecord { game_id: String, ggf: String },
+
+    /// 棋譜の読み込み完了通知
+    #[serde(rename = "games_imported")]
+    GamesImported { game_ids: Vec<String> },
+
+    /// 局面の定石手と統計
+    #[serde(rename = "opening_explorer")]
+    OpeningExplorer {
+        /// 対称な局面で共通となるZobristキー（16進数）
+        canonical_key: String,
+        /// この局面を通った対局の数
+        games: u32,
+        /// 定石手（対局数の多い順）
+        moves: Vec<BookMove>,
     },
+
+    /// 局面の合法手の評価
+    #[serde(rename = "hint")]
+    Hint {
+        /// 合法手（評価値の高い順）
+        moves: Vec<RankedMove>,
+        /// 終局まで読み切った値か（falseの場合は途中までの評価値）
+        exact: bool,
+        /// この対局で残っているヒントの回数（局面の検討の場合はNone）
+        hints_remaining: Option<usize>,
+    },
+
+    /// 対局の解析結果（評価値の推移と主な悪手）
+    #[serde(rename = "analysis_report")]
+    AnalysisReport { report: AnalysisReport },
+}
+
+impl ServerMessage {
+    /// 指定した色のプレイヤー向けのゲーム状態メッセージを作成
+    ///
+    /// `canonical_key`には`Game::canonical_key`の値を渡す
+    pub fn game_state(state: &GameState, canonical_key: u64, your_color: DiskColor) -> Self {
+        ServerMessage::GameState {
+            board: state.board.clone(),
+            board_size: state.board_size,
+            variant: state.variant,
+            current_player: state.current_player.clone(),
+            your_color: your_color.to_string(),
+            legal_moves: state.legal_moves.iter().map(|&(x, y)| [x, y]).collect(),
+            holes: state.holes.iter().map(|&(x, y)| [x, y]).collect(),
+            black_can_move: state.black_can_move,
+            white_can_move: state.white_can_move,
+            last_move: state.last_move.map(|(x, y)| [x, y]),
+            flipped: state.flipped.iter().map(|&(x, y)| [x, y]).collect(),
+            black_count: state.black_count,
+            white_count: state.white_count,
+            position_key: format!("{:016x}", state.position_key),
+            canonical_key: format!("{:016x}", canonical_key),
+            clock: state.clock.clone(),
+        }
+    }
+
+    /// ゲームのエラーをエラーコード付きのエラーメッセージに変換
+    pub fn game_error(error: &GameError) -> Self {
+        ServerMessage::Error {
+            message: error.to_string(),
+            code: Some(error.code().to_string()),
+        }
+    }
+
+    /// 対局の結果からゲーム終了メッセージを作成
+    pub fn game_over(game: &Game, result: &GameResult) -> Self {
+        ServerMessage::GameOver {
+            winner: result
+                .winner
+                .map(|color| game.player_name(color).to_string()),
+            reason: result.termination.to_string(),
+            result: *result,
+            transcript: game.transcript(),
+            opening: game.opening.clone(),
+            handicap: game.handicap(),
+        }
+    }
+}
+
+/// 過去のゲームの概要
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub game_id: String,
+    pub black: String,
+    pub white: String,
+    pub winner: Option<String>,
+    /// 対局の結果（途中までの棋譜の場合はNone）
+    pub result: Option<GameResult>,
+    pub board_size: usize,
+    pub transcript: String,
+    /// 開始局面までの手順（XOTやランダム開始の場合）
+    pub opening: Option<String>,
+    /// 置き石（ハンディキャップなしの場合はNone）
+    pub handicap: Option<Handicap>,
+    /// レーティングに反映しない対局か
+    pub casual: bool,
+    /// 対局中に使ったヒント
+    pub hints: Vec<HintRecord>,
+    pub played_at: String,
+}
+
+impl GameSummary {
+    pub fn new(game: &Game) -> Self {
+        Self {
+            game_id: game.game_id.clone(),
+            black: game.black_player_name.clone(),
+            white: game.white_player_name.clone(),
+            winner: game
+                .winner_color()
+                .map(|color| game.player_name(color).to_string()),
+            result: game.result(),
+            board_size: game.config().board_size,
+            transcript: game.transcript(),
+            opening: game.opening.clone(),
+            handicap: game.handicap(),
+            casual: game.config().casual,
+            hints: game.hints().to_vec(),
+            played_at: game.created_at.to_rfc3339(),
+        }
+    }
 }
 
 /// WebSocketセッションの接続メッセージ
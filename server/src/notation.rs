@@ -0,0 +1,277 @@
+//! # 棋譜表記
+//!
+//! オセロの標準座標表記（列をa〜、行を1〜で表す。例: `f5`）の解析と整形
+//!
+//! ## 関数
+//!
+//! - `format_square`:       座標を表記に変換
+//! - `parse_square`:        表記を座標に変換
+//! - `format_action`:       手を表記に変換（パスは`pa`）
+//! - `parse_action`:        表記を手に変換
+//! - `format_actions`:      手の一覧を連結した棋譜文字列に変換（例: `f5d6c3`）
+//! - `format_transcript`:   ゲームの履歴を棋譜文字列に変換
+//! - `parse_transcript`:    棋譜文字列を手の一覧に変換
+//! - `format_board_string`: 盤面を盤面文字列に変換（例: `---...-OX---...---`）
+//! - `parse_board_string`:  盤面文字列を盤面に変換
+
+use crate::bitboard::{Board, SUPPORTED_BOARD_SIZES};
+use crate::game::{DiskColor, GameMove, MoveAction};
+
+/// パスを表す表記
+pub const PASS_TOKEN: &str = "pa";
+
+/// パスとして受け付ける表記
+const PASS_ALIASES: [&str; 3] = ["pa", "pass", "--"];
+
+/// 座標を表記に変換（`(5, 4)` -> `f5`）
+pub fn format_square(x: usize, y: usize) -> String {
+    format!("{}{}", (b'a' + x as u8) as char, y + 1)
+}
+
+/// 表記を座標に変換（`f5` -> `(5, 4)`）
+pub fn parse_square(text: &str, board_size: usize) -> Result<(usize, usize), String> {
+    let text = text.trim().to_ascii_lowercase();
+    let mut chars = text.chars();
+
+    let column = chars
+        .next()
+        .filter(|c| c.is_ascii_lowercase())
+        .ok_or_else(|| format!("Invalid square: {}", text))?;
+    // 行番号は先頭が0でない数字だけ（`+5`や`05`は受け付けない）
+    let digits = chars.as_str();
+    let row: usize = Some(digits)
+        .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+        .filter(|digits| !digits.starts_with('0'))
+        .and_then(|digits| digits.parse().ok())
+        .ok_or_else(|| format!("Invalid square: {}", text))?;
+
+    let x = (column as u8 - b'a') as usize;
+    if x >= board_size || row == 0 || row > board_size {
+        return Err(format!("Square out of board: {}", text));
+    }
+
+    Ok((x, row - 1))
+}
+
+/// 手を表記に変換
+pub fn format_action(action: &MoveAction) -> String {
+    match action {
+        MoveAction::Place { x, y } => format_square(*x, *y),
+        MoveAction::Pass => PASS_TOKEN.to_string(),
+    }
+}
+
+/// 表記を手に変換（パスは`pa`、`pass`、`--`を受け付ける）
+pub fn parse_action(text: &str, board_size: usize) -> Result<MoveAction, String> {
+    let text = text.trim();
+    if PASS_ALIASES
+        .iter()
+        .any(|alias| text.eq_ignore_ascii_case(alias))
+    {
+        return Ok(MoveAction::Pass);
+    }
+
+    let (x, y) = parse_square(text, board_size)?;
+    Ok(MoveAction::Place { x, y })
+}
+
+/// 手の一覧を棋譜文字列に変換
+///
+/// パスは手番から自明なため省略する
+pub fn format_actions(actions: &[MoveAction]) -> String {
+    actions
+        .iter()
+        .filter(|&&action| action != MoveAction::Pass)
+        .map(format_action)
+        .collect()
+}
+
+/// ゲームの履歴を棋譜文字列に変換（パスは省略する）
+pub fn format_transcript(moves: &[GameMove]) -> String {
+    let actions: Vec<MoveAction> = moves.iter().map(|game_move| game_move.action).collect();
+    format_actions(&actions)
+}
+
+/// 棋譜文字列を手の一覧に変換
+///
+/// 手の間の空白は無視し、パスの表記（`pa`、`--`）はパスとして扱う
+pub fn parse_transcript(text: &str, board_size: usize) -> Result<Vec<MoveAction>, String> {
+    let chars: Vec<char> = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let mut actions = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        // パス
+        if chars[i..].starts_with(&['p', 'a']) || chars[i..].starts_with(&['-', '-']) {
+            actions.push(MoveAction::Pass);
+            i += 2;
+            continue;
+        }
+
+        // 列の文字に続く行番号（10以上の盤面では2桁）
+        let start = i;
+        i += 1;
+        while i < chars.len() && chars[i].is_ascii_digit() {
+            i += 1;
+        }
+
+        let token: String = chars[start..i].iter().collect();
+        let (x, y) = parse_square(&token, board_size)?;
+        actions.push(MoveAction::Place { x, y });
+    }
+
+    Ok(actions)
+}
+
+/// 盤面を盤面文字列に変換（a1から行ごとに`X`=黒、`O`=白、`-`=空き、`#`=穴）
+pub fn format_board_string(board: &Board) -> String {
+    let size = board.size();
+    (0..size * size)
+        .map(|index| {
+            let (x, y) = (index % size, index / size);
+            match board.get(x, y) {
+                Some(DiskColor::Black) => 'X',
+                Some(DiskColor::White) => 'O',
+                None if board.is_hole(x, y) => '#',
+                None => '-',
+            }
+        })
+        .collect()
+}
+
+/// 盤面文字列を盤面に変換
+///
+/// 空白は無視し、盤面サイズはマスの数（8x8なら64文字）から判定する。
+/// 黒は`X`・`*`、白は`O`、空きは`-`・`.`、穴は`#`を受け付ける
+pub fn parse_board_string(text: &str) -> Result<Board, String> {
+    let cells: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let size = SUPPORTED_BOARD_SIZES
+        .into_iter()
+        .find(|size| size * size == cells.len())
+        .ok_or_else(|| {
+            format!(
+                "Invalid board string length: {} (expected 36, 64, 100 or 144 squares)",
+                cells.len()
+            )
+        })?;
+
+    let mut board = Board::empty(size);
+    for (index, cell) in cells.iter().enumerate() {
+        let (x, y) = (index % size, index / size);
+        let disk = match cell {
+            'X' | 'x' | '*' => Some(DiskColor::Black),
+            'O' | 'o' => Some(DiskColor::White),
+            '-' | '.' => None,
+            '#' => {
+                board.set_hole(x, y);
+                continue;
+            }
+            _ => return Err(format!("Invalid board string character: {}", cell)),
+        };
+        board.set(x, y, disk);
+    }
+
+    Ok(board)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn square_round_trip() {
+        for size in SUPPORTED_BOARD_SIZES {
+            for y in 0..size {
+                for x in 0..size {
+                    assert_eq!(parse_square(&format_square(x, y), size), Ok((x, y)));
+                }
+            }
+        }
+        assert_eq!(format_square(5, 4), "f5");
+        assert_eq!(parse_square(" F5 ", 8), Ok((5, 4)));
+        assert_eq!(format_square(11, 11), "l12");
+    }
+
+    #[test]
+    fn square_out_of_board() {
+        assert!(parse_square("i1", 8).is_err());
+        assert!(parse_square("a9", 8).is_err());
+        assert!(parse_square("a0", 8).is_err());
+        assert!(parse_square("a00", 8).is_err());
+        assert!(parse_square("5f", 8).is_err());
+        assert!(parse_square("", 8).is_err());
+        assert_eq!(parse_square("a10", 10), Ok((0, 9)));
+    }
+
+    #[test]
+    fn malformed_row_is_rejected() {
+        for text in ["f+5", "f05", "f-5", "f 5", "f5x", "f", "f٥", "f+10"] {
+            assert_eq!(
+                parse_square(text, 10),
+                Err(format!("Invalid square: {}", text)),
+                "{}",
+                text
+            );
+        }
+        assert!(parse_transcript("f05", 8).is_err());
+        assert!(parse_action("f+5", 8).is_err());
+    }
+
+    #[test]
+    fn pass_aliases() {
+        for text in ["pa", "PA", "pass", "--"] {
+            assert_eq!(parse_action(text, 8), Ok(MoveAction::Pass));
+        }
+        assert_eq!(format_action(&MoveAction::Pass), PASS_TOKEN);
+    }
+
+    #[test]
+    fn transcript_round_trip() {
+        let actions = parse_transcript("f5 d6 C3 pa d3", 8).unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                MoveAction::Place { x: 5, y: 4 },
+                MoveAction::Place { x: 3, y: 5 },
+                MoveAction::Place { x: 2, y: 2 },
+                MoveAction::Pass,
+                MoveAction::Place { x: 3, y: 2 },
+            ]
+        );
+        // パスは省略される
+        assert_eq!(format_actions(&actions), "f5d6c3d3");
+
+        // 10x10以上では2桁の行番号が続く
+        let actions = parse_transcript("a10j1e10", 10).unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                MoveAction::Place { x: 0, y: 9 },
+                MoveAction::Place { x: 9, y: 0 },
+                MoveAction::Place { x: 4, y: 9 },
+            ]
+        );
+        assert_eq!(format_actions(&actions), "a10j1e10");
+    }
+
+    #[test]
+    fn board_string_round_trip() {
+        let mut board = Board::initial(8);
+        board.set_hole(0, 0);
+        let text = format_board_string(&board);
+        assert_eq!(text.len(), 64);
+        assert_eq!(&text[27..29], "OX");
+        assert!(text.starts_with('#'));
+
+        let parsed = parse_board_string(&text).unwrap();
+        assert_eq!(parsed, board);
+        assert!(parsed.is_hole(0, 0));
+
+        assert!(parse_board_string("XO-").is_err());
+        assert!(parse_board_string(&"?".repeat(64)).is_err());
+    }
+}
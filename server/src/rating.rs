@@ -0,0 +1,49 @@
+//! # レーティング
+//!
+//! 対局の結果からイロレーティングを更新する
+//!
+//! 置き石のある対局では、置き石に相当するレーティング差を受け取った側に加えて
+//! 期待得点を計算する（置き石で勝っても強い相手に勝ったほどは上がらない）
+//!
+//! ## 関数
+//!
+//! - `expected_score`: レーティングから期待得点を計算
+//! - `update`:         対局の結果から両者の新しいレーティングを計算
+
+use crate::game::{DiskColor, GameResult, Handicap};
+
+/// 初めて対局するプレイヤーのレーティング
+pub const DEFAULT_RATING: u32 = 1000;
+/// 1局で変動するレーティングの最大幅
+const K_FACTOR: f64 = 32.0;
+
+/// `rating`のプレイヤーが`opponent`の相手に対して得る期待得点（0.0〜1.0）
+pub fn expected_score(rating: u32, opponent: u32) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent as f64 - rating as f64) / 400.0))
+}
+
+/// 対局の結果から黒と白の新しいレーティングを計算
+pub fn update(
+    black: u32,
+    white: u32,
+    result: &GameResult,
+    handicap: Option<Handicap>,
+) -> (u32, u32) {
+    // 置き石を受け取った側は、その分だけ強いものとして期待得点を計算する
+    let (mut effective_black, mut effective_white) = (black, white);
+    if let Some(handicap) = handicap {
+        match handicap.color {
+            DiskColor::Black => effective_black += handicap.rating_equivalent(),
+            DiskColor::White => effective_white += handicap.rating_equivalent(),
+        }
+    }
+
+    let expected = expected_score(effective_black, effective_white);
+    let delta = K_FACTOR * (result.outcome(DiskColor::Black) - expected);
+
+    (adjust(black, delta), adjust(white, -delta))
+}
+
+fn adjust(rating: u32, delta: f64) -> u32 {
+    (rating as f64 + delta).round().max(0.0) as u32
+}
@@ -1,19 +1,43 @@
 use actix::prelude::*;
-use std::collections::HashMap;
-use std::time::Duration;
-
-use crate::game::{Game, GameState};
+use rand::Rng;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::ai::{self, Computer, Difficulty, Engine, RankedMove};
+use crate::analysis::{self, AnalysisReport};
+use crate::archive::GameArchive;
+use crate::book::{BookSource, OpeningBook};
+use crate::error::GameError;
+use crate::game::{DiskColor, Game, GameConfig, GameState, MoveAction, Variant};
+use crate::ggf;
 use crate::matchmaking::MatchmakingService;
-use crate::message::{ClientMessage, Connect, Disconnect, SendMessage, ServerMessage};
+use crate::message::{
+    ClientMessage, Connect, Disconnect, GameSummary, MoveInput, SendMessage, ServerMessage,
+};
+use crate::notation;
+use crate::rating::{self, DEFAULT_RATING};
 use crate::session::UserStatus;
-use crate::{app_log, debug_log, info_log, warning_log};
+use crate::zobrist;
+use crate::{app_log, debug_log, error_log, info_log, warning_log};
 
 // マッチングのタイムアウト（秒）
 const MATCH_TIMEOUT: Duration = Duration::from_secs(30);
 // マッチングメイキングの実行間隔（ミリ秒）
 const MATCHMAKING_INTERVAL: Duration = Duration::from_millis(1000);
-// デフォルトレーティング
-const DEFAULT_RATING: u32 = 1000;
+// 対局時計の確認間隔（ミリ秒）
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+// コンピューターが1手に使う残り時間の割合（残り時間をこの数で割る）
+const COMPUTER_TIME_DIVISOR: u64 = 10;
+// ヒントと局面の検討で合法手を評価する時間
+const HINT_BUDGET: Duration = Duration::from_secs(1);
+// 対局の解析で1局面を評価する時間
+const ANALYSIS_BUDGET: Duration = Duration::from_millis(200);
+// 保持する解析結果の数（超えた分は古いものから捨てる）
+const MAX_ANALYSES: usize = 256;
+// 1回の読み込みで受け付ける棋譜の数
+const MAX_IMPORT_GAMES: usize = 100;
 
 /// ゲームサーバーアクター - 全セッションとゲーム状態を管理
 pub struct GameServer {
@@ -29,6 +53,32 @@ pub struct GameServer {
     active_games: HashMap<String, Game>,
     /// ユーザーID -> ゲームIDのマッピング
     user_games: HashMap<String, String>,
+    /// 終了したゲームと読み込んだ棋譜
+    archive: GameArchive,
+    /// 終了したゲームと読み込んだ棋譜から集計した定石
+    book: OpeningBook,
+    /// ユーザー名 -> レーティング
+    ratings: HashMap<String, u32>,
+    /// コンピューターとの対局 (ゲームID -> (コンピューターの色, 強さ, プレイヤー))
+    ///
+    /// 思考中は別スレッドからも参照するため共有する
+    computers: HashMap<String, (DiskColor, Difficulty, Arc<Mutex<Computer>>)>,
+    /// 対局の解析結果 (ゲームID -> 解析結果)
+    analyses: HashMap<String, AnalysisReport>,
+    /// 局面を評価中のセッション（1セッションで同時に1つまで）
+    pending_hints: HashSet<String>,
+    /// 解析結果を保持した順のゲームID
+    analysis_order: VecDeque<String>,
+    /// 解析中の対局 (ゲームID -> 結果を待っているセッションID)
+    pending_analyses: HashMap<String, Vec<String>>,
+    /// 終了した対局を自動で解析するか（既定では要求された対局だけを解析する）
+    auto_analysis: bool,
+}
+
+impl Default for GameServer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GameServer {
@@ -40,18 +90,31 @@ impl GameServer {
             matchmaking: MatchmakingService::new(),
             active_games: HashMap::new(),
             user_games: HashMap::new(),
+            archive: GameArchive::new(),
+            book: OpeningBook::new(),
+            ratings: HashMap::new(),
+            computers: HashMap::new(),
+            analyses: HashMap::new(),
+            pending_hints: HashSet::new(),
+            analysis_order: VecDeque::new(),
+            pending_analyses: HashMap::new(),
+            auto_analysis: false,
         }
     }
 
+    /// 終了した対局の自動解析を有効または無効にする（解析の要求にはいつでも応じる）
+    pub fn with_auto_analysis(mut self, enabled: bool) -> Self {
+        self.auto_analysis = enabled;
+        self
+    }
+
     /// 全クライアントにメッセージをブロードキャスト
     fn broadcast_message(&self, message: &ServerMessage, skip_id: Option<&str>) {
         let msg = serde_json::to_string(message).unwrap();
 
         for (id, (_, addr)) in &self.sessions {
-            if let Some(skip) = skip_id {
-                if id == skip {
-                    continue;
-                }
+            if skip_id == Some(id.as_str()) {
+                continue;
             }
             addr.do_send(SendMessage {
                 message: msg.clone(),
@@ -74,14 +137,31 @@ impl GameServer {
         debug_log!("Updated user status: {} -> {:?}", user_id, status);
     }
 
+    /// ユーザーのレーティング（未対局の場合は初期値）
+    fn rating(&self, username: &str) -> u32 {
+        self.ratings
+            .get(username)
+            .copied()
+            .unwrap_or(DEFAULT_RATING)
+    }
+
     /// マッチングメイキングキューにユーザーを追加
-    fn add_user_to_matchmaking(&mut self, session_id: &str) -> Result<(), &'static str> {
+    fn add_user_to_matchmaking(
+        &mut self,
+        session_id: &str,
+        config: GameConfig,
+    ) -> Result<(), String> {
+        // ゲーム設定を確認
+        config.validate()?;
+
         if let Some((username, _)) = self.sessions.get(session_id) {
             // ユーザーがキューに追加された場合はステータスを更新
+            let rating = self.rating(username);
             if self.matchmaking.add_to_queue(
                 session_id.to_string(),
                 username.clone(),
-                DEFAULT_RATING,
+                rating,
+                config,
             ) {
                 self.update_user_status(session_id, UserStatus::SearchingMatch);
 
@@ -92,10 +172,10 @@ impl GameServer {
 
                 Ok(())
             } else {
-                Err("Already in matchmaking queue")
+                Err("Already in matchmaking queue".to_string())
             }
         } else {
-            Err("User session not found")
+            Err("User session not found".to_string())
         }
     }
 
@@ -135,10 +215,13 @@ impl GameServer {
                 created_match.player1_name.clone(),
                 created_match.player2_id.clone(),
                 created_match.player2_name.clone(),
+                created_match.config.clone(),
+                created_match.handicap,
             );
 
             // ゲームの初期状態を取得
             let game_state = game.get_state();
+            let canonical_key = game.canonical_key();
 
             // ゲームをアクティブゲームマップに登録
             self.active_games.insert(game_id.clone(), game);
@@ -151,32 +234,32 @@ impl GameServer {
 
             // プレイヤー1にマッチが見つかったことを通知
             let msg1 = ServerMessage::MatchFound {
+                game_id: game_id.clone(),
                 opponent: created_match.player2_name.clone(),
+                variant: created_match.config.variant,
+                handicap: created_match.handicap,
             };
             self.send_message_to_session(&created_match.player1_id, &msg1);
             self.update_user_status(&created_match.player1_id, UserStatus::InGame);
 
             // プレイヤー1に初期ゲーム状態を送信
-            let black_state = ServerMessage::GameState {
-                board: game_state.board.clone(),
-                current_player: game_state.current_player.clone(),
-                your_color: "black".to_string(),
-            };
+            let black_state =
+                ServerMessage::game_state(&game_state, canonical_key, DiskColor::Black);
             self.send_message_to_session(&created_match.player1_id, &black_state);
 
             // プレイヤー2にマッチが見つかったことを通知
             let msg2 = ServerMessage::MatchFound {
+                game_id: game_id.clone(),
                 opponent: created_match.player1_name.clone(),
+                variant: created_match.config.variant,
+                handicap: created_match.handicap,
             };
             self.send_message_to_session(&created_match.player2_id, &msg2);
             self.update_user_status(&created_match.player2_id, UserStatus::InGame);
 
             // プレイヤー2に初期ゲーム状態を送信
-            let white_state = ServerMessage::GameState {
-                board: game_state.board.clone(),
-                current_player: game_state.current_player.clone(),
-                your_color: "white".to_string(),
-            };
+            let white_state =
+                ServerMessage::game_state(&game_state, canonical_key, DiskColor::White);
             self.send_message_to_session(&created_match.player2_id, &white_state);
         }
 
@@ -194,6 +277,7 @@ impl GameServer {
             // プレイヤー1に通知
             let msg1 = ServerMessage::Error {
                 message: "Match timed out. Please join the queue again.".to_string(),
+                code: None,
             };
             self.send_message_to_session(&expired_match.player1_id, &msg1);
             self.update_user_status(&expired_match.player1_id, UserStatus::Idle);
@@ -201,6 +285,7 @@ impl GameServer {
             // プレイヤー2に通知
             let msg2 = ServerMessage::Error {
                 message: "Match timed out. Please join the queue again.".to_string(),
+                code: None,
             };
             self.send_message_to_session(&expired_match.player2_id, &msg2);
             self.update_user_status(&expired_match.player2_id, UserStatus::Idle);
@@ -208,28 +293,63 @@ impl GameServer {
     }
 
     /// ゲームの手を処理する
-    fn handle_game_move(&mut self, session_id: &str, x: usize, y: usize) -> Result<(), String> {
+    fn handle_game_move(
+        &mut self,
+        session_id: &str,
+        input: MoveInput,
+        ctx: &mut Context<Self>,
+    ) -> Result<(), GameError> {
         // プレイヤーがゲームに参加しているか確認
         let game_id = match self.user_games.get(session_id) {
             Some(id) => id.clone(),
-            None => return Err("You are not in a game".to_string()),
+            None => return Err(GameError::NotInGame),
         };
 
+        self.play_move(&game_id, session_id, input, ctx)?;
+
+        // コンピューターとの対局なら次の手を考えさせる
+        self.request_computer_move(&game_id, ctx);
+        Ok(())
+    }
+
+    /// ゲームに手を打ち、両プレイヤーに結果を送信する
+    fn play_move(
+        &mut self,
+        game_id: &str,
+        player_id: &str,
+        input: MoveInput,
+        ctx: &mut Context<Self>,
+    ) -> Result<(), GameError> {
         // ゲームが存在するか確認
-        let game = match self.active_games.get_mut(&game_id) {
+        let game = match self.active_games.get_mut(game_id) {
             Some(game) => game,
-            None => return Err("Game not found".to_string()),
+            None => return Err(GameError::GameNotFound),
         };
 
+        // 手番側の持ち時間が切れていれば手を受け付けずに終局させる
+        if let Some(game_state) = game.check_timeout(Instant::now()) {
+            self.send_game_state_to_players(game_id, &game_state);
+            self.handle_game_over(game_id, ctx);
+            return Ok(());
+        }
+
         // 手を打つ
-        match game.make_move(session_id, x, y) {
+        let history_len = game.move_history().len();
+        let result = match input {
+            MoveInput::Coordinates { x, y } => game.make_move(player_id, x, y),
+            MoveInput::Notation { notation } => game.make_move_notation(player_id, &notation),
+        };
+        match result {
             Ok(game_state) => {
                 // 両プレイヤーにゲーム状態を送信
-                self.send_game_state_to_players(&game_id, &game_state);
+                self.send_game_state_to_players(game_id, &game_state);
+
+                // この手によって発生したパスを通知
+                self.send_passes_to_players(game_id, history_len);
 
                 // ゲームが終了したか確認
                 if game_state.is_game_over {
-                    self.handle_game_over(&game_id, &game_state);
+                    self.handle_game_over(game_id, ctx);
                 }
 
                 Ok(())
@@ -238,18 +358,185 @@ impl GameServer {
         }
     }
 
+    /// コンピューターとの対局を開始する
+    fn start_computer_game(
+        &mut self,
+        session_id: &str,
+        config: GameConfig,
+        engine: Engine,
+        difficulty: Difficulty,
+        color: Option<DiskColor>,
+        ctx: &mut Context<Self>,
+    ) -> Result<(), String> {
+        // ゲーム設定を確認（コンピューターとの対局はレーティングに反映しない）
+        config.validate()?;
+        let config = GameConfig {
+            casual: true,
+            ..config
+        };
+
+        let username = self.session_username(session_id)?;
+        if self.user_games.contains_key(session_id) {
+            return Err("Already in a game".to_string());
+        }
+
+        // マッチングキューに入っていれば抜ける
+        self.matchmaking.remove_from_queue(session_id);
+
+        let color = color.unwrap_or_else(|| {
+            if rand::thread_rng().gen_bool(0.5) {
+                DiskColor::Black
+            } else {
+                DiskColor::White
+            }
+        });
+        let computer_id = format!("computer-{}", Uuid::new_v4());
+        let computer_name = match engine {
+            Engine::Minimax => format!("Computer ({})", difficulty),
+            Engine::Mcts => format!("Computer ({}, {})", difficulty, engine),
+        };
+        let (black, white) = match color {
+            DiskColor::Black => (
+                (session_id.to_string(), username),
+                (computer_id, computer_name),
+            ),
+            DiskColor::White => (
+                (computer_id, computer_name),
+                (session_id.to_string(), username),
+            ),
+        };
+
+        let game_id = Uuid::new_v4().to_string();
+        let computer = Computer::new(engine, difficulty, config.variant);
+        let game = Game::new(
+            game_id.clone(),
+            black.0,
+            black.1,
+            white.0,
+            white.1,
+            config,
+            None,
+        );
+        info_log!(
+            "Computer game started: {} vs. {} ({})",
+            game.black_player_name,
+            game.white_player_name,
+            game_id
+        );
+
+        let game_state = game.get_state();
+        let canonical_key = game.canonical_key();
+        let match_found = ServerMessage::MatchFound {
+            game_id: game_id.clone(),
+            opponent: game.player_name(color.opposite()).to_string(),
+            variant: game.config().variant,
+            handicap: None,
+        };
+
+        self.active_games.insert(game_id.clone(), game);
+        self.user_games
+            .insert(session_id.to_string(), game_id.clone());
+        self.computers.insert(
+            game_id.clone(),
+            (color.opposite(), difficulty, Arc::new(Mutex::new(computer))),
+        );
+
+        self.send_message_to_session(session_id, &match_found);
+        self.update_user_status(session_id, UserStatus::InGame);
+        self.send_message_to_session(
+            session_id,
+            &ServerMessage::game_state(&game_state, canonical_key, color),
+        );
+
+        // コンピューターが先手なら考え始める
+        self.request_computer_move(&game_id, ctx);
+        Ok(())
+    }
+
+    /// コンピューターの手番であれば別スレッドで手を考えさせる
+    ///
+    /// 思考中もアクターを止めないよう、選んだ手は`ComputerMove`としてアクターに送り返す
+    fn request_computer_move(&self, game_id: &str, ctx: &mut Context<Self>) {
+        let Some((color, difficulty, computer)) = self.computers.get(game_id) else {
+            return;
+        };
+        let Some(game) = self.active_games.get(game_id) else {
+            return;
+        };
+        let color = *color;
+        if game.is_over() || game.current_color() != color {
+            return;
+        }
+
+        let ply = game.move_history().len();
+        // 定石にある局面では考えずに定石手を打つ
+        if difficulty.uses_book()
+            && let Some((x, y)) = self.book.choose_move(
+                game.board(),
+                color,
+                game.config().variant,
+                &mut rand::thread_rng(),
+            )
+        {
+            debug_log!("Book move for game {}: ({}, {})", game_id, x, y);
+            ctx.address().do_send(ComputerMove {
+                game_id: game_id.to_string(),
+                ply,
+                x,
+                y,
+            });
+            return;
+        }
+
+        // 思考中も対局は進められるよう、局面は複製して渡す
+        let snapshot = game.clone();
+        let computer = Arc::clone(computer);
+        // 持ち時間がある場合は残り時間の一部だけを使う
+        let time_limit = game.clock_state().map(|clock| {
+            let left = match color {
+                DiskColor::Black => clock.black_ms,
+                DiskColor::White => clock.white_ms,
+            };
+            Duration::from_millis(left / COMPUTER_TIME_DIVISOR)
+        });
+
+        let addr = ctx.address();
+        let game_id = game_id.to_string();
+        actix::spawn(async move {
+            let chosen = tokio::task::spawn_blocking(move || {
+                computer
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .choose_move(&snapshot, time_limit, &mut rand::thread_rng())
+            })
+            .await;
+
+            match chosen {
+                Ok(Some((x, y))) => addr.do_send(ComputerMove { game_id, ply, x, y }),
+                Ok(None) => {}
+                Err(e) => {
+                    error_log!("Computer move failed for game {}: {}", game_id, e);
+                }
+            }
+        });
+    }
+
     /// ゲーム降参を処理する
-    fn handle_resign(&mut self, session_id: &str) -> Result<(), String> {
+    fn handle_resign(
+        &mut self,
+        session_id: &str,
+        ctx: &mut Context<Self>,
+    ) -> Result<(), GameError> {
         // プレイヤーがゲームに参加しているか確認
         let game_id = match self.user_games.get(session_id) {
             Some(id) => id.clone(),
-            None => return Err("You are not in a game".to_string()),
+            None => return Err(GameError::NotInGame),
         };
 
         // ゲームが存在するか確認
         let game = match self.active_games.get_mut(&game_id) {
             Some(game) => game,
-            None => return Err("Game not found".to_string()),
+            None => return Err(GameError::GameNotFound),
         };
 
         // 降参処理
@@ -259,7 +546,7 @@ impl GameServer {
                 self.send_game_state_to_players(&game_id, &game_state);
 
                 // ゲーム終了処理
-                self.handle_game_over(&game_id, &game_state);
+                self.handle_game_over(&game_id, ctx);
 
                 Ok(())
             }
@@ -270,74 +557,358 @@ impl GameServer {
     /// ゲーム状態を両プレイヤーに送信
     fn send_game_state_to_players(&self, game_id: &str, game_state: &GameState) {
         if let Some(game) = self.active_games.get(game_id) {
+            let canonical_key = game.canonical_key();
+
             // 黒プレイヤーにゲーム状態を送信
-            let black_state = ServerMessage::GameState {
-                board: game_state.board.clone(),
-                current_player: game_state.current_player.clone(),
-                your_color: "black".to_string(),
-            };
+            let black_state =
+                ServerMessage::game_state(game_state, canonical_key, DiskColor::Black);
             self.send_message_to_session(&game.black_player_id, &black_state);
 
             // 白プレイヤーにゲーム状態を送信
-            let white_state = ServerMessage::GameState {
-                board: game_state.board.clone(),
-                current_player: game_state.current_player.clone(),
-                your_color: "white".to_string(),
-            };
+            let white_state =
+                ServerMessage::game_state(game_state, canonical_key, DiskColor::White);
             self.send_message_to_session(&game.white_player_id, &white_state);
         }
     }
 
-    /// ゲーム終了処理
-    fn handle_game_over(&mut self, game_id: &str, game_state: &GameState) {
-        // 必要な情報を先に取り出す
-        let (black_player_id, white_player_id, _, _, winner_name) =
-            if let Some(game) = self.active_games.get(game_id) {
-                // 勝者を特定
-                let winner_name = match &game_state.winner {
-                    Some(player_id) => {
-                        if player_id == &game.black_player_id {
-                            Some(game.black_player_name.clone())
-                        } else {
-                            Some(game.white_player_name.clone())
-                        }
-                    }
-                    None => None, // 引き分け
+    /// 履歴の`from`番目以降に記録されたパスを両プレイヤーに通知
+    fn send_passes_to_players(&self, game_id: &str, from: usize) {
+        if let Some(game) = self.active_games.get(game_id) {
+            for game_move in &game.move_history()[from..] {
+                if game_move.action != MoveAction::Pass {
+                    continue;
+                }
+
+                let pass = ServerMessage::Pass {
+                    color: game_move.color.to_string(),
+                    player: game.player_name(game_move.color).to_string(),
                 };
+                self.send_message_to_session(&game.black_player_id, &pass);
+                self.send_message_to_session(&game.white_player_id, &pass);
+            }
+        }
+    }
 
-                (
-                    game.black_player_id.clone(),
-                    game.white_player_id.clone(),
-                    game.black_player_name.clone(),
-                    game.white_player_name.clone(),
-                    winner_name,
-                )
-            } else {
-                // ゲームが見つからない場合は何もしない
-                return;
-            };
+    /// 持ち時間が切れたゲームを時間切れ負けで終局させる
+    fn check_clocks(&mut self, ctx: &mut Context<Self>) {
+        let now = Instant::now();
+        let timed_out: Vec<(String, GameState)> = self
+            .active_games
+            .iter_mut()
+            .filter_map(|(game_id, game)| {
+                game.check_timeout(now)
+                    .map(|game_state| (game_id.clone(), game_state))
+            })
+            .collect();
+
+        for (game_id, game_state) in timed_out {
+            self.send_game_state_to_players(&game_id, &game_state);
+            self.handle_game_over(&game_id, ctx);
+        }
+    }
+
+    /// ゲーム終了処理
+    ///
+    /// 両プレイヤーに結果を通知し、レーティングを更新してゲームをアーカイブに保管する。
+    /// 自動解析が有効な場合は解析を始め、終わり次第接続中のプレイヤーに結果を送る
+    fn handle_game_over(&mut self, game_id: &str, ctx: &mut Context<Self>) {
+        let Some(game) = self.active_games.remove(game_id) else {
+            // ゲームが見つからない場合は何もしない
+            return;
+        };
+        let Some(result) = game.result() else {
+            warning_log!("Game {} is not over yet", game_id);
+            self.active_games.insert(game_id.to_string(), game);
+            return;
+        };
+        self.computers.remove(game_id);
 
-        info_log!("Game over: {}", game_id);
+        info_log!("Game over: {} ({})", game_id, result.termination);
 
         // 両プレイヤーにゲーム終了を通知
-        let game_over = ServerMessage::GameOver {
-            winner: winner_name,
-            reason: "Game completed".to_string(),
+        let game_over = ServerMessage::game_over(&game, &result);
+        self.send_message_to_session(&game.black_player_id, &game_over);
+        self.send_message_to_session(&game.white_player_id, &game_over);
+
+        for player_id in [&game.black_player_id, &game.white_player_id] {
+            // 接続中のプレイヤーのステータスを更新
+            if self.sessions.contains_key(player_id) {
+                self.update_user_status(player_id, UserStatus::Idle);
+            }
+
+            // ゲームの関連付けを解除
+            self.user_games.remove(player_id);
+        }
+
+        // レーティングを更新（カジュアル対局とコンピューターとの対局は反映しない）
+        if result.is_rated() && !game.config().casual {
+            let (black, white) = rating::update(
+                self.rating(&game.black_player_name),
+                self.rating(&game.white_player_name),
+                &result,
+                game.handicap(),
+            );
+            info_log!(
+                "Ratings updated: {} {}, {} {}",
+                game.black_player_name,
+                black,
+                game.white_player_name,
+                white
+            );
+            self.ratings.insert(game.black_player_name.clone(), black);
+            self.ratings.insert(game.white_player_name.clone(), white);
+        }
+
+        // 接続中のプレイヤーに向けて解析を始める
+        if self.auto_analysis {
+            let players = [&game.black_player_id, &game.white_player_id]
+                .into_iter()
+                .filter(|player_id| self.sessions.contains_key(*player_id))
+                .cloned()
+                .collect();
+            self.start_analysis(&game, players, ctx);
+        }
+
+        // 定石に集計してアーカイブに保管
+        self.book.add_game(&game, BookSource::Server);
+        self.archive.store(game, None);
+    }
+
+    /// 対局の解析結果を`waiters`のセッションに送る
+    ///
+    /// 解析済みであればすぐに送り、解析中であれば待つセッションに加え、
+    /// どちらでもなければ別スレッドで解析を始めて`AnalysisFinished`で受け取る
+    /// （失敗した場合は`AnalysisFailed`）
+    fn start_analysis(&mut self, game: &Game, waiters: Vec<String>, ctx: &mut Context<Self>) {
+        let game_id = game.game_id.clone();
+        if let Some(report) = self.analyses.get(&game_id) {
+            let message = ServerMessage::AnalysisReport {
+                report: report.clone(),
+            };
+            for session_id in &waiters {
+                self.send_message_to_session(session_id, &message);
+            }
+            return;
+        }
+        if let Some(pending) = self.pending_analyses.get_mut(&game_id) {
+            pending.extend(waiters);
+            return;
+        }
+
+        info_log!("Analysis started: {}", game_id);
+        self.pending_analyses.insert(game_id.clone(), waiters);
+
+        let game = game.clone();
+        let addr = ctx.address();
+        actix::spawn(async move {
+            let report =
+                tokio::task::spawn_blocking(move || analysis::analyze(&game, ANALYSIS_BUDGET))
+                    .await;
+
+            match report {
+                Ok(report) => addr.do_send(AnalysisFinished { report }),
+                Err(e) => {
+                    error_log!("Analysis failed for game {}: {}", game_id, e);
+                    addr.do_send(AnalysisFailed { game_id });
+                }
+            }
+        });
+    }
+
+    /// 過去の対局の解析結果を要求する
+    fn handle_request_analysis(
+        &mut self,
+        session_id: &str,
+        game_id: &str,
+        ctx: &mut Context<Self>,
+    ) -> Result<(), String> {
+        let username = self.session_username(session_id)?;
+        let game = self
+            .archive
+            .get(game_id)
+            .filter(|archived| archived.is_accessible_by(&username))
+            .map(|archived| archived.game.clone())
+            .ok_or_else(|| "Game not found".to_string())?;
+
+        self.start_analysis(&game, vec![session_id.to_string()], ctx);
+        Ok(())
+    }
+
+    fn session_username(&self, session_id: &str) -> Result<String, String> {
+        self.sessions
+            .get(session_id)
+            .map(|(username, _)| username.clone())
+            .ok_or_else(|| "User session not found".to_string())
+    }
+
+    /// 過去のゲーム一覧を送信
+    fn handle_list_games(&self, session_id: &str) -> Result<(), String> {
+        let username = self.session_username(session_id)?;
+        let games = self
+            .archive
+            .games_for_user(&username)
+            .into_iter()
+            .map(GameSummary::new)
+            .collect();
+
+        self.send_message_to_session(session_id, &ServerMessage::GameList { games });
+        Ok(())
+    }
+
+    /// 終了したゲームをGGF形式で送信
+    fn handle_export_game(&self, session_id: &str, game_id: &str) -> Result<(), String> {
+        let username = self.session_username(session_id)?;
+        let archived = self
+            .archive
+            .get(game_id)
+            .filter(|archived| archived.is_accessible_by(&username))
+            .ok_or_else(|| "Game not found".to_string())?;
+
+        let record = ServerMessage::GameRecord {
+            game_id: game_id.to_string(),
+            ggf: ggf::export_game(&archived.game),
         };
+        self.send_message_to_session(session_id, &record);
+        Ok(())
+    }
+
+    /// GGF形式の棋譜を読み込んでアーカイブに保管（1回に`MAX_IMPORT_GAMES`局まで）
+    fn handle_import_game(&mut self, session_id: &str, text: &str) -> Result<(), String> {
+        let username = self.session_username(session_id)?;
+        let games = ggf::import_games(text)?;
+        if games.len() > MAX_IMPORT_GAMES {
+            return Err(format!(
+                "Too many games to import: {} (at most {} at once)",
+                games.len(),
+                MAX_IMPORT_GAMES
+            ));
+        }
 
-        self.send_message_to_session(&black_player_id, &game_over);
-        self.send_message_to_session(&white_player_id, &game_over);
+        let game_ids = games.iter().map(|game| game.game_id.clone()).collect();
+        for game in games {
+            self.book.add_game(&game, BookSource::Imported);
+            self.archive.store(game, Some(username.clone()));
+        }
+        info_log!(
+            "Opening book: {} server games, {} imported games, {} positions",
+            self.book.game_count(BookSource::Server),
+            self.book.game_count(BookSource::Imported),
+            self.book.position_count()
+        );
+
+        self.send_message_to_session(session_id, &ServerMessage::GamesImported { game_ids });
+        Ok(())
+    }
+
+    /// 局面の合法手を評価して送信
+    ///
+    /// 盤面を指定しない場合は対局中の現在の局面のヒントとして扱う。評価は別スレッドで行い、
+    /// `HintEvaluated`で受け取ってから回数を消費し、履歴に記録する。
+    /// レーティングに反映する対局の最中は盤面を指定した評価も行わず、
+    /// 1つのセッションで同時に評価するのは1局面まで
+    fn handle_hint(
+        &mut self,
+        session_id: &str,
+        board: Option<&str>,
+        side: Option<DiskColor>,
+        variant: Option<Variant>,
+        ctx: &mut Context<Self>,
+    ) -> Result<(), GameError> {
+        // セッションがなければ結果の送り先もない
+        if !self.sessions.contains_key(session_id) {
+            return Ok(());
+        }
+        // 評価は重いため、前の要求の結果を返すまで次の要求を受け付けない
+        if self.pending_hints.contains(session_id) {
+            return Err(GameError::HintPending);
+        }
+
+        let active_game = self
+            .user_games
+            .get(session_id)
+            .and_then(|game_id| self.active_games.get(game_id));
+
+        let (board, side, variant, game) = match board {
+            Some(text) => {
+                if active_game.is_some_and(|game| !game.config().casual) {
+                    return Err(GameError::HintsNotAllowed);
+                }
+                (
+                    notation::parse_board_string(text).map_err(GameError::InvalidNotation)?,
+                    side.unwrap_or(DiskColor::Black),
+                    variant.unwrap_or_default(),
+                    None,
+                )
+            }
+            None => {
+                let game = active_game.ok_or(GameError::NotInGame)?;
+                game.check_hint(session_id)?;
+                (
+                    *game.board(),
+                    game.current_color(),
+                    game.config().variant,
+                    Some((game.game_id.clone(), game.move_history().len())),
+                )
+            }
+        };
 
-        // プレイヤーのステータスを更新
-        self.update_user_status(&black_player_id, UserStatus::Idle);
-        self.update_user_status(&white_player_id, UserStatus::Idle);
+        let session_id = session_id.to_string();
+        self.pending_hints.insert(session_id.clone());
+        let addr = ctx.address();
+        actix::spawn(async move {
+            let ranked = tokio::task::spawn_blocking(move || {
+                ai::rank_moves(&board, side, variant, HINT_BUDGET)
+            })
+            .await;
+
+            let result = ranked.map_err(|e| {
+                error_log!("Hint evaluation failed: {}", e);
+                "Hint evaluation failed".to_string()
+            });
+            addr.do_send(HintEvaluated {
+                session_id,
+                game,
+                result,
+            });
+        });
+        Ok(())
+    }
 
-        // ゲームの関連付けを解除
-        self.user_games.remove(&black_player_id);
-        self.user_games.remove(&white_player_id);
+    /// 局面の定石手と統計を送信
+    ///
+    /// 盤面を指定しない場合は対局中のゲームの現在の局面を使う
+    fn handle_explore_opening(
+        &self,
+        session_id: &str,
+        board: Option<&str>,
+        side: Option<DiskColor>,
+        variant: Option<Variant>,
+    ) -> Result<(), String> {
+        let (board, side, variant) = match board {
+            Some(text) => (
+                notation::parse_board_string(text)?,
+                side.unwrap_or(DiskColor::Black),
+                variant.unwrap_or_default(),
+            ),
+            None => {
+                let game = self
+                    .user_games
+                    .get(session_id)
+                    .and_then(|game_id| self.active_games.get(game_id))
+                    .ok_or_else(|| "Not in a game".to_string())?;
+                (*game.board(), game.current_color(), game.config().variant)
+            }
+        };
 
-        // ゲームをアクティブリストから削除
-        self.active_games.remove(game_id);
+        let (canonical_key, _) = zobrist::canonical_key(&board, side);
+        let moves = self.book.moves(&board, side, variant);
+        let explorer = ServerMessage::OpeningExplorer {
+            canonical_key: format!("{:016x}", canonical_key),
+            games: moves.iter().map(|mv| mv.games).sum(),
+            moves,
+        };
+        self.send_message_to_session(session_id, &explorer);
+        Ok(())
     }
 }
 
@@ -348,6 +919,57 @@ impl Message for RunMatchmaking {
     type Result = ();
 }
 
+/// 対局時計の定期確認
+struct CheckClocks;
+
+impl Message for CheckClocks {
+    type Result = ();
+}
+
+/// コンピューターが選んだ手
+struct ComputerMove {
+    game_id: String,
+    /// 考え始めた時点の手数（その後に局面が変わっていれば手を捨てる）
+    ply: usize,
+    x: usize,
+    y: usize,
+}
+
+impl Message for ComputerMove {
+    type Result = ();
+}
+
+/// 評価した局面の合法手
+struct HintEvaluated {
+    session_id: String,
+    /// 対局中のヒントの場合は(ゲームID, 評価を始めた時点の手数)
+    game: Option<(String, usize)>,
+    /// (評価値の高い順の合法手, 読み切ったか)
+    result: Result<(Vec<RankedMove>, bool), String>,
+}
+
+impl Message for HintEvaluated {
+    type Result = ();
+}
+
+/// 終了した対局の解析結果
+struct AnalysisFinished {
+    report: AnalysisReport,
+}
+
+impl Message for AnalysisFinished {
+    type Result = ();
+}
+
+/// 解析に失敗した対局
+struct AnalysisFailed {
+    game_id: String,
+}
+
+impl Message for AnalysisFailed {
+    type Result = ();
+}
+
 /// GameServerアクターの実装
 impl Actor for GameServer {
     type Context = Context<Self>;
@@ -359,6 +981,11 @@ impl Actor for GameServer {
         ctx.run_interval(MATCHMAKING_INTERVAL, |_, ctx| {
             ctx.address().do_send(RunMatchmaking);
         });
+
+        // 定期的に時間切れを確認
+        ctx.run_interval(CLOCK_CHECK_INTERVAL, |_, ctx| {
+            ctx.address().do_send(CheckClocks);
+        });
     }
 }
 
@@ -371,6 +998,15 @@ impl Handler<RunMatchmaking> for GameServer {
     }
 }
 
+/// 対局時計の定期確認処理
+impl Handler<CheckClocks> for GameServer {
+    type Result = ();
+
+    fn handle(&mut self, _: CheckClocks, ctx: &mut Self::Context) {
+        self.check_clocks(ctx);
+    }
+}
+
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct ClientMessageWrapper {
@@ -378,16 +1014,173 @@ pub struct ClientMessageWrapper {
     pub message: ClientMessage,
 }
 
+/// コンピューターの手のハンドラー
+impl Handler<ComputerMove> for GameServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: ComputerMove, ctx: &mut Self::Context) {
+        let Some(&(color, _, _)) = self.computers.get(&msg.game_id) else {
+            return;
+        };
+        let Some(game) = self.active_games.get(&msg.game_id) else {
+            return;
+        };
+        if game.move_history().len() != msg.ply {
+            return;
+        }
+
+        let player_id = game.player_id(color).to_string();
+        let input = MoveInput::Coordinates { x: msg.x, y: msg.y };
+        if let Err(e) = self.play_move(&msg.game_id, &player_id, input, ctx) {
+            warning_log!("Computer move rejected in game {}: {}", msg.game_id, e);
+            return;
+        }
+
+        // 相手がパスした場合は続けて考える
+        self.request_computer_move(&msg.game_id, ctx);
+    }
+}
+
+/// 局面の評価結果のハンドラー
+impl Handler<HintEvaluated> for GameServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: HintEvaluated, _: &mut Self::Context) {
+        self.pending_hints.remove(&msg.session_id);
+
+        // 評価に失敗した場合は回数を消費しない
+        let (moves, exact) = match msg.result {
+            Ok(ranked) => ranked,
+            Err(message) => {
+                let error_msg = ServerMessage::Error {
+                    message,
+                    code: None,
+                };
+                self.send_message_to_session(&msg.session_id, &error_msg);
+                return;
+            }
+        };
+
+        let hints_remaining = match &msg.game {
+            Some((game_id, ply)) => {
+                // 評価の間に局面が変わっていれば結果を捨てる
+                let Some(game) = self
+                    .active_games
+                    .get_mut(game_id)
+                    .filter(|game| game.move_history().len() == *ply)
+                else {
+                    let error_msg = ServerMessage::Error {
+                        message: "Position changed before the hint was ready".to_string(),
+                        code: None,
+                    };
+                    self.send_message_to_session(&msg.session_id, &error_msg);
+                    return;
+                };
+                match game.use_hint(&msg.session_id) {
+                    Ok(remaining) => Some(remaining),
+                    Err(e) => {
+                        self.send_message_to_session(
+                            &msg.session_id,
+                            &ServerMessage::game_error(&e),
+                        );
+                        return;
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let hint = ServerMessage::Hint {
+            moves,
+            exact,
+            hints_remaining,
+        };
+        self.send_message_to_session(&msg.session_id, &hint);
+    }
+}
+
+/// 対局の解析結果のハンドラー
+impl Handler<AnalysisFinished> for GameServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: AnalysisFinished, _: &mut Self::Context) {
+        let game_id = msg.report.game_id.clone();
+        info_log!(
+            "Analysis finished: {} ({} key mistakes)",
+            game_id,
+            msg.report.key_mistakes.len()
+        );
+
+        let message = ServerMessage::AnalysisReport {
+            report: msg.report.clone(),
+        };
+        for session_id in self.pending_analyses.remove(&game_id).unwrap_or_default() {
+            self.send_message_to_session(&session_id, &message);
+        }
+        if self.analyses.insert(game_id.clone(), msg.report).is_none() {
+            self.analysis_order.push_back(game_id);
+        }
+        while self.analysis_order.len() > MAX_ANALYSES {
+            if let Some(oldest) = self.analysis_order.pop_front() {
+                self.analyses.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// 対局の解析の失敗のハンドラー
+impl Handler<AnalysisFailed> for GameServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: AnalysisFailed, _: &mut Self::Context) {
+        // 待っているセッションに知らせ、次の要求で解析をやり直せるようにする
+        let message = ServerMessage::Error {
+            message: format!("Analysis failed for game {}", msg.game_id),
+            code: None,
+        };
+        for session_id in self
+            .pending_analyses
+            .remove(&msg.game_id)
+            .unwrap_or_default()
+        {
+            self.send_message_to_session(&session_id, &message);
+        }
+    }
+}
+
 /// クライアントメッセージハンドラー
 impl Handler<ClientMessageWrapper> for GameServer {
     type Result = ();
 
-    fn handle(&mut self, msg: ClientMessageWrapper, _: &mut Self::Context) {
+    fn handle(&mut self, msg: ClientMessageWrapper, ctx: &mut Self::Context) {
         match msg.message {
-            ClientMessage::JoinQueue => {
-                if let Err(e) = self.add_user_to_matchmaking(&msg.session_id) {
+            ClientMessage::JoinQueue { options } => {
+                if let Err(e) = self.add_user_to_matchmaking(&msg.session_id, options.into_config())
+                {
                     let error_msg = ServerMessage::Error {
-                        message: e.to_string(),
+                        message: e,
+                        code: None,
+                    };
+                    self.send_message_to_session(&msg.session_id, &error_msg);
+                }
+            }
+            ClientMessage::PlayComputer {
+                engine,
+                difficulty,
+                color,
+                options,
+            } => {
+                if let Err(e) = self.start_computer_game(
+                    &msg.session_id,
+                    options.into_config(),
+                    engine.unwrap_or_default(),
+                    difficulty.unwrap_or_default(),
+                    color,
+                    ctx,
+                ) {
+                    let error_msg = ServerMessage::Error {
+                        message: e,
+                        code: None,
                     };
                     self.send_message_to_session(&msg.session_id, &error_msg);
                 }
@@ -396,26 +1189,90 @@ impl Handler<ClientMessageWrapper> for GameServer {
                 if let Err(e) = self.remove_user_from_matchmaking(&msg.session_id) {
                     let error_msg = ServerMessage::Error {
                         message: e.to_string(),
+                        code: None,
                     };
                     self.send_message_to_session(&msg.session_id, &error_msg);
                 }
             }
-            ClientMessage::MakeMove { x, y } => {
-                match self.handle_game_move(&msg.session_id, x, y) {
+            ClientMessage::MakeMove(input) => {
+                match self.handle_game_move(&msg.session_id, input, ctx) {
                     Ok(_) => {}
                     Err(e) => {
-                        let error_msg = ServerMessage::Error { message: e };
+                        let error_msg = ServerMessage::game_error(&e);
                         self.send_message_to_session(&msg.session_id, &error_msg);
                     }
                 }
             }
-            ClientMessage::Resign => match self.handle_resign(&msg.session_id) {
+            ClientMessage::Resign => match self.handle_resign(&msg.session_id, ctx) {
                 Ok(_) => {}
                 Err(e) => {
-                    let error_msg = ServerMessage::Error { message: e };
+                    let error_msg = ServerMessage::game_error(&e);
                     self.send_message_to_session(&msg.session_id, &error_msg);
                 }
             },
+            ClientMessage::ListGames => {
+                if let Err(e) = self.handle_list_games(&msg.session_id) {
+                    let error_msg = ServerMessage::Error {
+                        message: e,
+                        code: None,
+                    };
+                    self.send_message_to_session(&msg.session_id, &error_msg);
+                }
+            }
+            ClientMessage::ExportGame { game_id } => {
+                if let Err(e) = self.handle_export_game(&msg.session_id, &game_id) {
+                    let error_msg = ServerMessage::Error {
+                        message: e,
+                        code: None,
+                    };
+                    self.send_message_to_session(&msg.session_id, &error_msg);
+                }
+            }
+            ClientMessage::ImportGame { ggf } => {
+                if let Err(e) = self.handle_import_game(&msg.session_id, &ggf) {
+                    let error_msg = ServerMessage::Error {
+                        message: e,
+                        code: None,
+                    };
+                    self.send_message_to_session(&msg.session_id, &error_msg);
+                }
+            }
+            ClientMessage::ExploreOpening {
+                board,
+                side,
+                variant,
+            } => {
+                if let Err(e) =
+                    self.handle_explore_opening(&msg.session_id, board.as_deref(), side, variant)
+                {
+                    let error_msg = ServerMessage::Error {
+                        message: e,
+                        code: None,
+                    };
+                    self.send_message_to_session(&msg.session_id, &error_msg);
+                }
+            }
+            ClientMessage::RequestHint {
+                board,
+                side,
+                variant,
+            } => {
+                if let Err(e) =
+                    self.handle_hint(&msg.session_id, board.as_deref(), side, variant, ctx)
+                {
+                    let error_msg = ServerMessage::game_error(&e);
+                    self.send_message_to_session(&msg.session_id, &error_msg);
+                }
+            }
+            ClientMessage::RequestAnalysis { game_id } => {
+                if let Err(e) = self.handle_request_analysis(&msg.session_id, &game_id, ctx) {
+                    let error_msg = ServerMessage::Error {
+                        message: e,
+                        code: None,
+                    };
+                    self.send_message_to_session(&msg.session_id, &error_msg);
+                }
+            }
             ClientMessage::Heartbeat => {
                 // ハートビートは無視
             }
@@ -439,6 +1296,7 @@ impl Handler<Connect> for GameServer {
                     message: "Your account has been logged in from another device or location.
                             If this wasn't you, please secure your account immediately."
                         .to_string(),
+                    code: None,
                 };
                 let msg_str = serde_json::to_string(&disconnect_msg).unwrap();
                 addr.do_send(SendMessage { message: msg_str });
@@ -472,7 +1330,7 @@ impl Handler<Connect> for GameServer {
 impl Handler<Disconnect> for GameServer {
     type Result = ();
 
-    fn handle(&mut self, msg: Disconnect, _: &mut Self::Context) -> Self::Result {
+    fn handle(&mut self, msg: Disconnect, ctx: &mut Self::Context) -> Self::Result {
         if let Some((username, _)) = self.sessions.remove(&msg.session_id) {
             self.users.remove(&username);
             self.user_statuses.remove(&msg.session_id);
@@ -482,40 +1340,21 @@ impl Handler<Disconnect> for GameServer {
 
             // プレイヤーがゲームに参加していた場合は処理
             if let Some(game_id) = self.user_games.get(&msg.session_id).cloned() {
-                if let Some(game) = self.active_games.get(&game_id) {
-                    // 相手プレイヤーを特定
-                    let opponent_id = if msg.session_id == game.black_player_id {
-                        game.white_player_id.clone()
-                    } else {
-                        game.black_player_id.clone()
-                    };
-
-                    let winner_name = if msg.session_id == game.black_player_id {
-                        game.white_player_name.clone()
-                    } else {
-                        game.black_player_name.clone()
-                    };
-
-                    // 相手プレイヤーにゲーム終了を通知
-                    let game_over = ServerMessage::GameOver {
-                        winner: Some(winner_name),
-                        reason: "Opponent disconnected".to_string(),
-                    };
-
-                    self.send_message_to_session(&opponent_id, &game_over);
-
-                    // 相手プレイヤーのステータスを更新
-                    self.update_user_status(&opponent_id, UserStatus::Idle);
-
-                    // ゲームの関連付けを解除
-                    self.user_games.remove(&opponent_id);
-                    self.user_games.remove(&msg.session_id);
-
-                    // ゲームをアクティブリストから削除
-                    self.active_games.remove(&game_id);
-                } else {
-                    // ゲームが見つからなかった場合は単に関連付けを解除
-                    self.user_games.remove(&msg.session_id);
+                // 切断したプレイヤーの負けとして終了させ、相手に通知する
+                let abandoned = self
+                    .active_games
+                    .get_mut(&game_id)
+                    .map(|game| game.abandon(&msg.session_id));
+                match abandoned {
+                    Some(Ok(_)) => self.handle_game_over(&game_id, ctx),
+                    Some(Err(e)) => {
+                        warning_log!("Failed to abandon game {}: {}", game_id, e);
+                        self.user_games.remove(&msg.session_id);
+                    }
+                    None => {
+                        // ゲームが見つからなかった場合は単に関連付けを解除
+                        self.user_games.remove(&msg.session_id);
+                    }
                 }
             }
 
@@ -43,6 +43,12 @@ pub enum UserStatus {
     Offline,
 }
 
+impl Default for WsGameSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl WsGameSession {
     pub fn new() -> Self {
         Self {
@@ -87,9 +93,8 @@ impl WsGameSession {
             error_log!("Couldn't create a success message: {}", e);
         });
 
-        match msg {
-            Ok(msg) => ctx.text(msg),
-            Err(_) => return,
+        if let Ok(msg) = msg {
+            ctx.text(msg);
         }
     }
 
@@ -97,14 +102,14 @@ impl WsGameSession {
     fn send_error(&self, ctx: &mut ws::WebsocketContext<Self>, message: &str) {
         let response = ServerMessage::Error {
             message: message.to_string(),
+            code: None,
         };
         let msg = serde_json::to_string(&response).map_err(|e| {
             error_log!("Couldn't create a error message: {}", e);
         });
 
-        match msg {
-            Ok(msg) => ctx.text(msg),
-            Err(_) => return,
+        if let Ok(msg) = msg {
+            ctx.text(msg);
         }
     }
 }
@@ -0,0 +1,581 @@
+//! # 終盤の完全読み
+//!
+//! 空きマスが少ない局面（`MAX_EMPTIES`以下）を終局まで読み切り、
+//! 双方が最善を尽くしたときの最終的な石差と最善手を求める
+//!
+//! - 石差は`FinalScore::completed`と同じく空きマスを勝者に与えて数える
+//!   （アンチリバーシでは相手の石の数を自分の得点とする）
+//! - αβ法（PVS）で探索し、置換表の最善手の次は相手の合法手が少なくなる手から順に読む
+//! - 空きマスが多い局面では置換表で同じ局面の再探索を省く
+//! - 持ち時間を指定した場合、読み切れないまま時間を使い切るとエラーを返す
+//!
+//! ## 関数
+//!
+//! - `solve`:       局面を読み切って最善手と石差を求める
+//! - `solve_moves`: 合法手ごとに読み切った石差を求める
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::time::{Duration, Instant};
+
+use crate::bitboard::{Bitboard, Bits, Board, Geometry};
+use crate::game::{DiskColor, Variant};
+
+/// 読み切りを行う空きマスの最大数
+pub const MAX_EMPTIES: usize = 20;
+/// 手の並べ替えを行う空きマスの最小数（これより少ない局面ではそのままの順で読む）
+const ORDERING_MIN_EMPTIES: u32 = 5;
+/// 置換表を使う空きマスの最小数
+const TABLE_MIN_EMPTIES: u32 = 7;
+/// 置換表に保存する局面の最大数
+const TABLE_CAPACITY: usize = 1 << 22;
+/// 石差の範囲外の値
+const INFINITY: i32 = 1000;
+/// 持ち時間を確認する間隔（局面数）
+const DEADLINE_CHECK_INTERVAL: u64 = 4096;
+
+/// 読み切りの結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Solution {
+    /// 最善手（打てる手がなくパスする場合や終局している場合はNone）
+    pub best_move: Option<(usize, usize)>,
+    /// 手番側から見た最終的な石差
+    pub margin: i32,
+    /// 探索した局面の数
+    pub nodes: u64,
+}
+
+/// 合法手ごとの読み切りの結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SolvedMove {
+    /// 手の座標
+    pub square: (usize, usize),
+    /// この手を打ったときの手番側から見た最終的な石差
+    pub margin: i32,
+}
+
+/// 置換表に保存した値の種類
+#[derive(Debug, Clone, Copy)]
+enum Bound {
+    /// 正確な値
+    Exact,
+    /// 下限（βカットした値）
+    Lower,
+    /// 上限（αを超えなかった値）
+    Upper,
+}
+
+/// 置換表の項目
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    value: i32,
+    bound: Bound,
+    /// 最善手のビット位置
+    best: usize,
+}
+
+/// 置換表のキー（ビット集合）向けの軽量なハッシュ関数
+#[derive(Default)]
+struct BitsHasher(u64);
+
+impl Hasher for BitsHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = (self.0.rotate_left(5) ^ value).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+}
+
+/// 1回の読み切りの探索状態
+struct Search<B: Bits> {
+    geometry: &'static Geometry<B>,
+    holes: B,
+    variant: Variant,
+    nodes: u64,
+    /// (手番側の石, 相手の石) -> 置換表の項目
+    table: HashMap<(B, B), Entry, BuildHasherDefault<BitsHasher>>,
+    /// 探索を打ち切る時刻（時間制限なしの場合はNone）
+    deadline: Option<Instant>,
+    /// 持ち時間を使い切って探索を打ち切ったか（打ち切った後の値は使えない）
+    aborted: bool,
+}
+
+impl<B: Bits> Search<B> {
+    fn new(board: &Bitboard<B>, variant: Variant, time_limit: Option<Duration>) -> Self {
+        Self {
+            geometry: board.geometry(),
+            holes: board.holes(),
+            variant,
+            nodes: 0,
+            table: HashMap::default(),
+            deadline: time_limit.map(|limit| Instant::now() + limit),
+            aborted: false,
+        }
+    }
+
+    /// 一定の局面数ごとに持ち時間を確認し、使い切っていれば打ち切る
+    #[inline]
+    fn check_deadline(&mut self) -> bool {
+        if !self.aborted
+            && self.nodes.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+            && self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.aborted = true;
+        }
+        self.aborted
+    }
+
+    /// 打ち切った場合はエラー、それ以外は`value`を返す
+    fn finish<T>(&self, value: T) -> Result<T, String> {
+        if self.aborted {
+            return Err(format!("Solver ran out of time after {} nodes", self.nodes));
+        }
+        Ok(value)
+    }
+
+    #[inline]
+    fn empties(&self, player: B, opponent: B) -> B {
+        self.geometry.full & !(player | opponent | self.holes)
+    }
+
+    /// 終局時の手番側から見た石差（空きマスは勝者に与える）
+    fn final_margin(&self, player: B, opponent: B) -> i32 {
+        let (mine, theirs) = match self.variant {
+            Variant::Standard => (player.count() as i32, opponent.count() as i32),
+            Variant::AntiReversi => (opponent.count() as i32, player.count() as i32),
+        };
+        let empties = self.empties(player, opponent).count() as i32;
+
+        match mine.cmp(&theirs) {
+            std::cmp::Ordering::Greater => mine - theirs + empties,
+            std::cmp::Ordering::Less => mine - theirs - empties,
+            std::cmp::Ordering::Equal => 0,
+        }
+    }
+
+    /// `index`に打った後の(手番側の石, 相手の石)
+    #[inline]
+    fn play(&self, player: B, opponent: B, index: usize) -> (B, B) {
+        let bit = B::bit(index);
+        let flipped = self.geometry.flips(player, opponent, bit);
+        (player | bit | flipped, opponent & !flipped)
+    }
+
+    /// `first`の手を先頭に、残りを相手の合法手が少なくなる順に並べた合法手
+    fn ordered(&self, player: B, opponent: B, moves: B, first: Option<usize>) -> Vec<usize> {
+        let mut scored: Vec<(u32, usize)> = moves
+            .indices()
+            .map(|index| {
+                if Some(index) == first {
+                    return (0, index);
+                }
+                let (next_player, next_opponent) = self.play(player, opponent, index);
+                let mobility = self
+                    .geometry
+                    .legal_moves(next_opponent, next_player, self.holes)
+                    .count();
+                (mobility + 1, index)
+            })
+            .collect();
+        scored.sort_unstable();
+        scored.into_iter().map(|(_, index)| index).collect()
+    }
+
+    /// 手番側から見た最終的な石差（`alpha`以下や`beta`以上の場合はその範囲の値）
+    fn negamax(
+        &mut self,
+        player: B,
+        opponent: B,
+        mut alpha: i32,
+        mut beta: i32,
+        passed: bool,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.check_deadline() {
+            return 0;
+        }
+
+        let empty_squares = self.empties(player, opponent);
+        if empty_squares.count() == 1 {
+            return self.last_move(player, opponent, empty_squares);
+        }
+
+        let moves = self.geometry.legal_moves(player, opponent, self.holes);
+        if moves.is_zero() {
+            // 両者とも打てなければ終局
+            if passed {
+                return self.final_margin(player, opponent);
+            }
+            return -self.negamax(opponent, player, -beta, -alpha, true);
+        }
+
+        let empties = empty_squares.count();
+        if empties < ORDERING_MIN_EMPTIES {
+            // 終局間際は並べ替えや置換表よりそのまま読む方が速い
+            let mut best = -INFINITY;
+            for index in moves.indices() {
+                let (next_player, next_opponent) = self.play(player, opponent, index);
+                let value = -self.negamax(next_opponent, next_player, -beta, -alpha, false);
+                best = best.max(value);
+                alpha = alpha.max(value);
+                if alpha >= beta {
+                    break;
+                }
+            }
+            return best;
+        }
+
+        let use_table = empties >= TABLE_MIN_EMPTIES;
+        let original_alpha = alpha;
+        let mut first = None;
+        if use_table && let Some(&entry) = self.table.get(&(player, opponent)) {
+            match entry.bound {
+                Bound::Exact => return entry.value,
+                Bound::Lower => alpha = alpha.max(entry.value),
+                Bound::Upper => beta = beta.min(entry.value),
+            }
+            if alpha >= beta {
+                return entry.value;
+            }
+            first = Some(entry.best);
+        }
+
+        let mut best = -INFINITY;
+        let mut best_index = 0;
+        for (i, index) in self
+            .ordered(player, opponent, moves, first)
+            .into_iter()
+            .enumerate()
+        {
+            let value = self.child_value(player, opponent, index, alpha, beta, i > 0);
+            if self.aborted {
+                return 0;
+            }
+            if value > best {
+                best = value;
+                best_index = index;
+            }
+            alpha = alpha.max(value);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if use_table && self.table.len() < TABLE_CAPACITY {
+            let bound = if best <= original_alpha {
+                Bound::Upper
+            } else if best >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.table.insert(
+                (player, opponent),
+                Entry {
+                    value: best,
+                    bound,
+                    best: best_index,
+                },
+            );
+        }
+
+        best
+    }
+
+    /// 空きマスが残り1つの局面の手番側から見た最終的な石差
+    ///
+    /// 合法手の生成を省き、最後のマスに手番側、打てなければ相手が打てるかだけを調べる
+    fn last_move(&self, player: B, opponent: B, square: B) -> i32 {
+        let flipped = self.geometry.flips(player, opponent, square);
+        if !flipped.is_zero() {
+            return self.final_margin(player | square | flipped, opponent & !flipped);
+        }
+
+        let flipped = self.geometry.flips(opponent, player, square);
+        if !flipped.is_zero() {
+            return -self.final_margin(opponent | square | flipped, player & !flipped);
+        }
+
+        self.final_margin(player, opponent)
+    }
+
+    /// `index`に打った後の局面の手番側から見た値
+    ///
+    /// `null_window`の場合はまず`alpha`を超えるかだけを調べ、超えたときのみ読み直す
+    fn child_value(
+        &mut self,
+        player: B,
+        opponent: B,
+        index: usize,
+        alpha: i32,
+        beta: i32,
+        null_window: bool,
+    ) -> i32 {
+        let (next_player, next_opponent) = self.play(player, opponent, index);
+        if null_window {
+            let value = -self.negamax(next_opponent, next_player, -alpha - 1, -alpha, false);
+            if value <= alpha || value >= beta {
+                return value;
+            }
+        }
+        -self.negamax(next_opponent, next_player, -beta, -alpha, false)
+    }
+}
+
+/// 空きマスが多すぎないか確認
+fn check_empties(board: &Board) -> Result<(), String> {
+    let empties = board.empty_count();
+    if empties > MAX_EMPTIES {
+        return Err(format!(
+            "Too many empty squares to solve: {} (max {})",
+            empties, MAX_EMPTIES
+        ));
+    }
+    Ok(())
+}
+
+fn solve_bitboard<B: Bits>(
+    board: &Bitboard<B>,
+    color: DiskColor,
+    variant: Variant,
+    time_limit: Option<Duration>,
+) -> Result<Solution, String> {
+    let mut search = Search::new(board, variant, time_limit);
+    let (player, opponent) = (board.discs(color), board.discs(color.opposite()));
+    let moves = search.geometry.legal_moves(player, opponent, search.holes);
+
+    // 打てる手がなければパスまたは終局
+    if moves.is_zero() {
+        let margin = search.negamax(player, opponent, -INFINITY, INFINITY, false);
+        return search.finish(Solution {
+            best_move: None,
+            margin,
+            nodes: search.nodes,
+        });
+    }
+
+    let mut best = None;
+    let mut alpha = -INFINITY;
+    for index in search.ordered(player, opponent, moves, None) {
+        let value = search.child_value(player, opponent, index, alpha, INFINITY, best.is_some());
+        if search.aborted {
+            break;
+        }
+        if value > alpha {
+            alpha = value;
+            best = Some(index);
+        }
+    }
+
+    search.finish(Solution {
+        best_move: best.map(|index| board.coordinates(index)),
+        margin: alpha,
+        nodes: search.nodes,
+    })
+}
+
+fn solve_moves_bitboard<B: Bits>(
+    board: &Bitboard<B>,
+    color: DiskColor,
+    variant: Variant,
+    time_limit: Option<Duration>,
+) -> Result<Vec<SolvedMove>, String> {
+    let mut search = Search::new(board, variant, time_limit);
+    let (player, opponent) = (board.discs(color), board.discs(color.opposite()));
+    let moves = search.geometry.legal_moves(player, opponent, search.holes);
+
+    let mut solved = Vec::new();
+    for index in search.ordered(player, opponent, moves, None) {
+        let (next_player, next_opponent) = search.play(player, opponent, index);
+        let margin = -search.negamax(next_opponent, next_player, -INFINITY, INFINITY, false);
+        if search.aborted {
+            break;
+        }
+        solved.push(SolvedMove {
+            square: board.coordinates(index),
+            margin,
+        });
+    }
+    solved.sort_by_key(|solved| std::cmp::Reverse(solved.margin));
+    search.finish(solved)
+}
+
+/// `color`の手番の局面を読み切って最善手と石差を求める
+///
+/// `time_limit`を指定すると、その時間内に読み切れなかった場合はエラーを返す
+pub fn solve(
+    board: &Board,
+    color: DiskColor,
+    variant: Variant,
+    time_limit: Option<Duration>,
+) -> Result<Solution, String> {
+    check_empties(board)?;
+    match board {
+        Board::Narrow(board) => solve_bitboard(board, color, variant, time_limit),
+        Board::Wide(board) => solve_bitboard(board, color, variant, time_limit),
+    }
+}
+
+/// `color`の手番の局面で合法手ごとに読み切った石差を求める（石差の大きい順）
+///
+/// `time_limit`の扱いは`solve`と同じ
+pub fn solve_moves(
+    board: &Board,
+    color: DiskColor,
+    variant: Variant,
+    time_limit: Option<Duration>,
+) -> Result<Vec<SolvedMove>, String> {
+    check_empties(board)?;
+    match board {
+        Board::Narrow(board) => solve_moves_bitboard(board, color, variant, time_limit),
+        Board::Wide(board) => solve_moves_bitboard(board, color, variant, time_limit),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::FinalScore;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+
+    /// 盤面を直接たどる素朴なミニマックスで求めた手番側から見た石差
+    fn brute_force(board: &Board, color: DiskColor, variant: Variant) -> i32 {
+        let moves = board.legal_moves(color);
+        if moves.is_empty() {
+            if !board.has_legal_move(color.opposite()) {
+                let (own, opponent) = FinalScore::completed(board, variant).points(color);
+                return own as i32 - opponent as i32;
+            }
+            return -brute_force(board, color.opposite(), variant);
+        }
+
+        moves
+            .into_iter()
+            .map(|(x, y)| {
+                let mut next = *board;
+                next.play(color, x, y);
+                -brute_force(&next, color.opposite(), variant)
+            })
+            .max()
+            .unwrap()
+    }
+
+    /// 初期配置から無作為に打ち進めて空きマスが`empties`になった局面と手番
+    fn random_position(
+        size: usize,
+        holes: &[(usize, usize)],
+        empties: usize,
+        rng: &mut StdRng,
+    ) -> Option<(Board, DiskColor)> {
+        let mut board = Board::initial(size);
+        for &(x, y) in holes {
+            board.set_hole(x, y);
+        }
+        let mut color = DiskColor::Black;
+
+        while board.empty_count() > empties {
+            let moves = board.legal_moves(color);
+            match moves.choose(rng) {
+                Some(&(x, y)) => {
+                    board.play(color, x, y);
+                }
+                None if !board.has_legal_move(color.opposite()) => return None,
+                None => {}
+            }
+            color = color.opposite();
+        }
+        Some((board, color))
+    }
+
+    fn check_against_brute_force(size: usize, holes: &[(usize, usize)], empties: usize) {
+        let mut rng = StdRng::seed_from_u64(size as u64 * 100 + empties as u64);
+        let mut checked = 0;
+
+        while checked < 8 {
+            let Some((board, color)) = random_position(size, holes, empties, &mut rng) else {
+                continue;
+            };
+            for variant in [Variant::Standard, Variant::AntiReversi] {
+                let expected = brute_force(&board, color, variant);
+                let solution = solve(&board, color, variant, None).unwrap();
+                assert_eq!(solution.margin, expected, "{:?} {}", variant, board.size());
+
+                // 合法手ごとの石差も素朴な探索と一致し、最善手はその最大になる
+                let solved = solve_moves(&board, color, variant, None).unwrap();
+                assert_eq!(solved.len(), board.legal_moves(color).len());
+                for mv in &solved {
+                    let (x, y) = mv.square;
+                    let mut next = board;
+                    next.play(color, x, y);
+                    assert_eq!(mv.margin, -brute_force(&next, color.opposite(), variant));
+                    if Some(mv.square) == solution.best_move {
+                        assert_eq!(mv.margin, expected);
+                    }
+                }
+            }
+            checked += 1;
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_on_8x8() {
+        check_against_brute_force(8, &[], 8);
+    }
+
+    #[test]
+    fn matches_brute_force_on_other_sizes_and_holes() {
+        check_against_brute_force(6, &[], 8);
+        check_against_brute_force(8, &[(0, 0), (7, 7)], 7);
+        check_against_brute_force(10, &[], 7);
+    }
+
+    #[test]
+    fn solved_moves_are_sorted_and_agree_with_solve() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let (board, color) = random_position(8, &[], 12, &mut rng).unwrap();
+        let solution = solve(&board, color, Variant::Standard, None).unwrap();
+        let moves = solve_moves(&board, color, Variant::Standard, None).unwrap();
+
+        assert!(
+            moves
+                .windows(2)
+                .all(|pair| pair[0].margin >= pair[1].margin)
+        );
+        assert_eq!(moves[0].margin, solution.margin);
+        assert!(
+            moves
+                .iter()
+                .any(|mv| Some(mv.square) == solution.best_move && mv.margin == solution.margin)
+        );
+    }
+
+    #[test]
+    fn too_many_empties_is_rejected() {
+        let board = Board::initial(8);
+        assert!(solve(&board, DiskColor::Black, Variant::Standard, None).is_err());
+    }
+
+    #[test]
+    fn aborts_when_out_of_time() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let (board, color) = random_position(8, &[], MAX_EMPTIES, &mut rng).unwrap();
+        let limit = Some(Duration::ZERO);
+        assert!(solve(&board, color, Variant::Standard, limit).is_err());
+        assert!(solve_moves(&board, color, Variant::Standard, limit).is_err());
+    }
+}
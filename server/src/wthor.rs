@@ -0,0 +1,352 @@
+//! # WTHORデータベース
+//!
+//! フランスオセロ連盟が公開しているWTHOR形式のバイナリデータベースを読み込む
+//!
+//! - `.wtb`: 対局データ（16バイトのヘッダーと68バイトの対局レコード）
+//! - `.JOU`: プレイヤー名（16バイトのヘッダーと20バイトの名前レコード）
+//! - `.TRN`: 大会名（16バイトのヘッダーと26バイトの名前レコード）
+//!
+//! 対局の手は`Game::make_move`で再生し、不正な手を含む対局はエラーとして報告する
+
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::bitboard::DEFAULT_BOARD_SIZE;
+use crate::game::{Game, GameConfig};
+use crate::notation;
+
+/// ヘッダーのバイト数
+const HEADER_LEN: usize = 16;
+/// 8x8の対局レコードのバイト数
+const GAME_RECORD_LEN: usize = 68;
+/// 対局レコードの手の数
+const MOVE_COUNT: usize = 60;
+/// プレイヤー名レコードのバイト数
+pub const PLAYER_RECORD_LEN: usize = 20;
+/// 大会名レコードのバイト数
+pub const TOURNAMENT_RECORD_LEN: usize = 26;
+
+/// WTHORファイルのヘッダー
+#[derive(Debug, Clone)]
+pub struct WthorHeader {
+    /// ファイルの作成日
+    pub created_on: Option<NaiveDate>,
+    /// 対局レコードの数（`.wtb`）
+    pub game_count: u32,
+    /// 名前レコードの数（`.JOU`、`.TRN`）
+    pub record_count: u16,
+    /// 対局が行われた年
+    pub game_year: u16,
+    /// 盤面の一辺の長さ
+    pub board_size: usize,
+    /// ソリテアの対局か
+    pub solitaire: bool,
+    /// 理論スコアの計算深さ
+    pub theoretical_depth: u8,
+}
+
+/// WTHORの対局レコード
+#[derive(Debug, Clone)]
+pub struct WthorGame {
+    /// 大会番号
+    pub tournament_id: u16,
+    /// 黒プレイヤー番号
+    pub black_player_id: u16,
+    /// 白プレイヤー番号
+    pub white_player_id: u16,
+    /// 黒の実際の石数
+    pub black_score: u8,
+    /// 黒の理論石数
+    pub theoretical_score: u8,
+    /// 手の一覧（パスは含まれない）
+    pub moves: Vec<(usize, usize)>,
+}
+
+/// 再生できなかった対局
+#[derive(Debug, Clone)]
+pub struct WthorError {
+    /// ファイル内の対局の番号（0始まり）
+    pub index: usize,
+    /// 不正だった手の番号（1始まり）
+    pub ply: usize,
+    /// エラー内容
+    pub message: String,
+}
+
+/// `.wtb`ファイルの読み込み結果
+pub struct WthorImport {
+    pub header: WthorHeader,
+    /// 再生できた対局
+    pub games: Vec<Game>,
+    /// 再生できなかった対局
+    pub errors: Vec<WthorError>,
+}
+
+/// ヘッダーを読み込む
+pub fn read_header(data: &[u8]) -> Result<WthorHeader, String> {
+    if data.len() < HEADER_LEN {
+        return Err("WTHOR file is too short for a header".to_string());
+    }
+
+    let year = data[0] as i32 * 100 + data[1] as i32;
+    let board_size = match data[12] {
+        0 | 8 => 8,
+        size => size as usize,
+    };
+
+    Ok(WthorHeader {
+        created_on: NaiveDate::from_ymd_opt(year, data[2] as u32, data[3] as u32),
+        game_count: u32::from_le_bytes([data[4], data[5], data[6], data[7]]),
+        record_count: u16::from_le_bytes([data[8], data[9]]),
+        game_year: u16::from_le_bytes([data[10], data[11]]),
+        board_size,
+        solitaire: data[13] == 1,
+        theoretical_depth: data[14],
+    })
+}
+
+/// `.wtb`ファイルの対局レコードを読み込む
+pub fn read_games(data: &[u8]) -> Result<(WthorHeader, Vec<WthorGame>), String> {
+    let header = read_header(data)?;
+    if header.board_size != DEFAULT_BOARD_SIZE {
+        return Err(format!(
+            "Unsupported WTHOR board size: {}",
+            header.board_size
+        ));
+    }
+
+    let expected = HEADER_LEN + header.game_count as usize * GAME_RECORD_LEN;
+    if data.len() < expected {
+        return Err(format!(
+            "WTHOR file is truncated: expected {} bytes, got {}",
+            expected,
+            data.len()
+        ));
+    }
+
+    let games = data[HEADER_LEN..expected]
+        .chunks_exact(GAME_RECORD_LEN)
+        .map(|record| WthorGame {
+            tournament_id: u16::from_le_bytes([record[0], record[1]]),
+            black_player_id: u16::from_le_bytes([record[2], record[3]]),
+            white_player_id: u16::from_le_bytes([record[4], record[5]]),
+            black_score: record[6],
+            theoretical_score: record[7],
+            // 手は`10 * 行 + 列`（a1 = 11、h8 = 88）、0は終局
+            moves: record[8..8 + MOVE_COUNT]
+                .iter()
+                .take_while(|&&square| square != 0)
+                .map(|&square| {
+                    let x = (square % 10) as usize;
+                    let y = (square / 10) as usize;
+                    (x.wrapping_sub(1), y.wrapping_sub(1))
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok((header, games))
+}
+
+/// `.JOU`・`.TRN`ファイルの名前レコードを読み込む
+///
+/// `record_len`には`PLAYER_RECORD_LEN`または`TOURNAMENT_RECORD_LEN`を指定する
+pub fn read_names(data: &[u8], record_len: usize) -> Result<Vec<String>, String> {
+    let header = read_header(data)?;
+    let expected = HEADER_LEN + header.record_count as usize * record_len;
+    if data.len() < expected {
+        return Err(format!(
+            "WTHOR file is truncated: expected {} bytes, got {}",
+            expected,
+            data.len()
+        ));
+    }
+
+    let names = data[HEADER_LEN..expected]
+        .chunks_exact(record_len)
+        .map(|record| {
+            // NUL終端のLatin-1文字列
+            record
+                .iter()
+                .take_while(|&&byte| byte != 0)
+                .map(|&byte| byte as char)
+                .collect::<String>()
+                .trim()
+                .to_string()
+        })
+        .collect();
+
+    Ok(names)
+}
+
+/// `.wtb`ファイルの対局を`Game`として再生する
+///
+/// `players`に`.JOU`ファイルのプレイヤー名を渡すと対局者名に使用する
+pub fn import_games(data: &[u8], players: Option<&[String]>) -> Result<WthorImport, String> {
+    let (header, records) = read_games(data)?;
+    let mut games = Vec::new();
+    let mut errors = Vec::new();
+
+    let player_name = |id: u16| {
+        players
+            .and_then(|names| names.get(id as usize))
+            .cloned()
+            .unwrap_or_else(|| format!("Player #{}", id))
+    };
+    let played_at = NaiveDate::from_ymd_opt(header.game_year as i32, 1, 1)
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|date| date.and_utc());
+
+    for (index, record) in records.iter().enumerate() {
+        let game_id = Uuid::new_v4().to_string();
+        let mut game = Game::new(
+            game_id.clone(),
+            format!("{}:black", game_id),
+            player_name(record.black_player_id),
+            format!("{}:white", game_id),
+            player_name(record.white_player_id),
+            GameConfig::default(),
+            None,
+        );
+        game.created_at = played_at.unwrap_or_else(Utc::now);
+
+        match replay(&mut game, &record.moves) {
+            Ok(()) => games.push(game),
+            Err((ply, message)) => errors.push(WthorError {
+                index,
+                ply,
+                message,
+            }),
+        }
+    }
+
+    Ok(WthorImport {
+        header,
+        games,
+        errors,
+    })
+}
+
+/// 手の一覧を再生（失敗した場合は手の番号とエラー内容を返す）
+fn replay(game: &mut Game, moves: &[(usize, usize)]) -> Result<(), (usize, String)> {
+    for (i, &(x, y)) in moves.iter().enumerate() {
+        if game.is_over() {
+            return Err((i + 1, "Moves recorded after the game ended".to_string()));
+        }
+
+        let color = game.current_color();
+        let player_id = game.player_id(color).to_string();
+        game.make_move(&player_id, x, y).map_err(|e| {
+            let square = if x < DEFAULT_BOARD_SIZE && y < DEFAULT_BOARD_SIZE {
+                notation::format_square(x, y)
+            } else {
+                "??".to_string()
+            };
+            (i + 1, format!("{} by {}: {}", square, color, e))
+        })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::MoveAction;
+
+    /// 作成日2001年2月3日、対局年2000年のヘッダー
+    fn header(game_count: u32, record_count: u16) -> Vec<u8> {
+        let mut data = vec![20, 1, 2, 3];
+        data.extend(game_count.to_le_bytes());
+        data.extend(record_count.to_le_bytes());
+        data.extend(2000u16.to_le_bytes());
+        data.extend([8, 0, 22, 0]);
+        data
+    }
+
+    /// 標準座標表記の手順から対局レコードを作る
+    fn record(black: u16, white: u16, moves: &[&str]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend(7u16.to_le_bytes());
+        data.extend(black.to_le_bytes());
+        data.extend(white.to_le_bytes());
+        data.extend([33, 34]);
+        let mut squares = [0u8; MOVE_COUNT];
+        for (square, text) in squares.iter_mut().zip(moves) {
+            let (x, y) = notation::parse_square(text, 8).unwrap();
+            *square = (10 * (y + 1) + x + 1) as u8;
+        }
+        data.extend(squares);
+        data
+    }
+
+    #[test]
+    fn decode_header_and_records() {
+        let mut data = header(2, 0);
+        data.extend(record(1, 2, &["f5", "d6", "c3"]));
+        data.extend(record(3, 4, &["a1", "h8"]));
+
+        let (header, games) = read_games(&data).unwrap();
+        assert_eq!(header.created_on, NaiveDate::from_ymd_opt(2001, 2, 3));
+        assert_eq!(header.game_count, 2);
+        assert_eq!(header.game_year, 2000);
+        assert_eq!(header.board_size, 8);
+        assert!(!header.solitaire);
+        assert_eq!(header.theoretical_depth, 22);
+
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].tournament_id, 7);
+        assert_eq!(games[0].black_player_id, 1);
+        assert_eq!(games[0].white_player_id, 2);
+        assert_eq!(games[0].black_score, 33);
+        assert_eq!(games[0].theoretical_score, 34);
+        assert_eq!(games[0].moves, vec![(5, 4), (3, 5), (2, 2)]);
+        assert_eq!(games[1].moves, vec![(0, 0), (7, 7)]);
+    }
+
+    #[test]
+    fn truncated_file() {
+        let mut data = header(2, 0);
+        data.extend(record(1, 2, &["f5"]));
+        assert!(read_games(&data).is_err());
+        assert!(read_header(&data[..10]).is_err());
+    }
+
+    #[test]
+    fn decode_names() {
+        let mut data = header(0, 2);
+        for name in ["Tamenori Hideshi", "Shaman Brian"] {
+            let mut record = [0u8; PLAYER_RECORD_LEN];
+            record[..name.len()].copy_from_slice(name.as_bytes());
+            data.extend(record);
+        }
+        assert_eq!(
+            read_names(&data, PLAYER_RECORD_LEN).unwrap(),
+            vec!["Tamenori Hideshi", "Shaman Brian"]
+        );
+    }
+
+    #[test]
+    fn replay_games_and_report_errors() {
+        let mut data = header(2, 0);
+        data.extend(record(0, 1, &["f5", "d6", "c3"]));
+        // 2手目のf5は既に石がある
+        data.extend(record(1, 0, &["f5", "f5"]));
+        let players = vec!["alice".to_string(), "bob".to_string()];
+
+        let import = import_games(&data, Some(&players)).unwrap();
+        assert_eq!(import.games.len(), 1);
+        let game = &import.games[0];
+        assert_eq!(game.transcript(), "f5d6c3");
+        assert_eq!(game.black_player_name, "alice");
+        assert_eq!(game.white_player_name, "bob");
+        assert!(matches!(
+            game.move_history()[0].action,
+            MoveAction::Place { x: 5, y: 4 }
+        ));
+
+        assert_eq!(import.errors.len(), 1);
+        assert_eq!(import.errors[0].index, 1);
+        assert_eq!(import.errors[0].ply, 2);
+    }
+}
@@ -0,0 +1,291 @@
+//! # Zobristハッシュ
+//!
+//! 局面ごとに安定した64ビットのキーを計算する
+//!
+//! キーは「盤面サイズ」「各マスの石」「穴のマス」「手番」に割り当てた乱数のXORで、
+//! 石を置く・反転させるたびに差分だけを更新できる。
+//! 乱数は固定のシードから生成するため、プロセスをまたいでも同じ局面は同じキーになる
+//!
+//! ## 関数
+//!
+//! - `disc_key`:      マスに置かれた石のキー
+//! - `side_key`:      手番のキー
+//! - `board_key`:     盤面全体のキー（手番を含まない）
+//! - `position_key`:  盤面と手番のキー
+//! - `canonical_key`: 盤面の8通りの対称形で最小となるキー
+
+use crate::bitboard::Board;
+use crate::game::DiskColor;
+
+/// 扱える最大のマス数（12x12）
+const MAX_SQUARES: usize = 144;
+/// 乱数生成のシード
+const SEED: u64 = 0x5eed_0f0e_11ed_2025;
+
+/// SplitMix64による疑似乱数列
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+/// 乱数テーブル
+struct Keys {
+    /// 色ごと・マスごとのキー
+    discs: [[u64; MAX_SQUARES]; 2],
+    /// 白番のキー
+    side: u64,
+    /// 盤面サイズごとのキー
+    sizes: [u64; 13],
+    /// 穴のマスごとのキー
+    holes: [u64; MAX_SQUARES],
+}
+
+const fn generate() -> Keys {
+    let mut state = SEED;
+    let mut discs = [[0; MAX_SQUARES]; 2];
+    let mut color = 0;
+    while color < 2 {
+        let mut square = 0;
+        while square < MAX_SQUARES {
+            let (next, key) = splitmix64(state);
+            state = next;
+            discs[color][square] = key;
+            square += 1;
+        }
+        color += 1;
+    }
+
+    let (next, side) = splitmix64(state);
+    state = next;
+
+    let mut sizes = [0; 13];
+    let mut size = 0;
+    while size < sizes.len() {
+        let (next, key) = splitmix64(state);
+        state = next;
+        sizes[size] = key;
+        size += 1;
+    }
+
+    let mut holes = [0; MAX_SQUARES];
+    let mut square = 0;
+    while square < MAX_SQUARES {
+        let (next, key) = splitmix64(state);
+        state = next;
+        holes[square] = key;
+        square += 1;
+    }
+
+    Keys {
+        discs,
+        side,
+        sizes,
+        holes,
+    }
+}
+
+static KEYS: Keys = generate();
+
+/// マス`index`（`y * size + x`）に置かれた石のキー
+#[inline]
+pub fn disc_key(color: DiskColor, index: usize) -> u64 {
+    match color {
+        DiskColor::Black => KEYS.discs[0][index],
+        DiskColor::White => KEYS.discs[1][index],
+    }
+}
+
+/// 手番のキー（黒番は0）
+#[inline]
+pub fn side_key(color: DiskColor) -> u64 {
+    match color {
+        DiskColor::Black => 0,
+        DiskColor::White => KEYS.side,
+    }
+}
+
+/// 盤面全体のキー（手番を含まない）
+///
+/// 穴のマスは対局中に変化しないため、盤面サイズのキーと同様に全体のキーにのみ含める
+pub fn board_key(board: &Board) -> u64 {
+    let size = board.size();
+    let mut key = KEYS.sizes[size];
+    for y in 0..size {
+        for x in 0..size {
+            if let Some(color) = board.get(x, y) {
+                key ^= disc_key(color, y * size + x);
+            } else if board.is_hole(x, y) {
+                key ^= KEYS.holes[y * size + x];
+            }
+        }
+    }
+    key
+}
+
+/// 盤面と手番のキー
+pub fn position_key(board: &Board, side_to_move: DiskColor) -> u64 {
+    board_key(board) ^ side_key(side_to_move)
+}
+
+/// 盤面の対称変換（回転と反転の8通り）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    FlipDiagonal,
+    FlipAntiDiagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::Identity,
+        Symmetry::Rotate90,
+        Symmetry::Rotate180,
+        Symmetry::Rotate270,
+        Symmetry::FlipHorizontal,
+        Symmetry::FlipVertical,
+        Symmetry::FlipDiagonal,
+        Symmetry::FlipAntiDiagonal,
+    ];
+
+    /// 座標を変換
+    pub fn apply(self, x: usize, y: usize, size: usize) -> (usize, usize) {
+        let last = size - 1;
+        match self {
+            Symmetry::Identity => (x, y),
+            Symmetry::Rotate90 => (last - y, x),
+            Symmetry::Rotate180 => (last - x, last - y),
+            Symmetry::Rotate270 => (y, last - x),
+            Symmetry::FlipHorizontal => (last - x, y),
+            Symmetry::FlipVertical => (x, last - y),
+            Symmetry::FlipDiagonal => (y, x),
+            Symmetry::FlipAntiDiagonal => (last - y, last - x),
+        }
+    }
+
+    /// 逆変換
+    pub fn inverse(self) -> Symmetry {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            other => other,
+        }
+    }
+
+    /// 盤面を変換
+    pub fn transform(self, board: &Board) -> Board {
+        let size = board.size();
+        let mut transformed = Board::empty(size);
+        for y in 0..size {
+            for x in 0..size {
+                let (tx, ty) = self.apply(x, y, size);
+                if let Some(color) = board.get(x, y) {
+                    transformed.set(tx, ty, Some(color));
+                } else if board.is_hole(x, y) {
+                    transformed.set_hole(tx, ty);
+                }
+            }
+        }
+        transformed
+    }
+}
+
+/// 盤面の8通りの対称形のうちキーが最小のものを選び、そのキーと変換を返す
+///
+/// 対称な局面はすべて同じキーになるため、定石データベースなどの索引に使える
+pub fn canonical_key(board: &Board, side_to_move: DiskColor) -> (u64, Symmetry) {
+    Symmetry::ALL
+        .into_iter()
+        .map(|symmetry| {
+            let key = position_key(&symmetry.transform(board), side_to_move);
+            (key, symmetry)
+        })
+        .min_by_key(|(key, _)| *key)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::SUPPORTED_BOARD_SIZES;
+    use crate::game::{Game, GameConfig};
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+
+    fn new_game(board_size: usize) -> Game {
+        Game::new(
+            "zobrist".to_string(),
+            "black-id".to_string(),
+            "black".to_string(),
+            "white-id".to_string(),
+            "white".to_string(),
+            GameConfig {
+                board_size,
+                ..GameConfig::default()
+            },
+            None,
+        )
+    }
+
+    #[test]
+    fn incremental_key_matches_full_recompute() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for size in SUPPORTED_BOARD_SIZES {
+            for _ in 0..5 {
+                let mut game = new_game(size);
+                while let Some(&(x, y)) = game.legal_moves().choose(&mut rng) {
+                    let player_id = game.player_id(game.current_color()).to_string();
+                    game.make_move(&player_id, x, y).unwrap();
+                    assert_eq!(
+                        game.position_key(),
+                        position_key(game.board(), game.current_color())
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn keys_depend_on_size_side_and_holes() {
+        let board = Board::initial(8);
+        assert_ne!(board_key(&board), board_key(&Board::initial(10)));
+        assert_ne!(
+            position_key(&board, DiskColor::Black),
+            position_key(&board, DiskColor::White)
+        );
+
+        let mut holed = board;
+        holed.set_hole(0, 0);
+        assert_ne!(board_key(&board), board_key(&holed));
+    }
+
+    #[test]
+    fn canonical_key_is_shared_by_symmetric_positions() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut game = new_game(8);
+        for _ in 0..12 {
+            let &(x, y) = game.legal_moves().choose(&mut rng).unwrap();
+            let player_id = game.player_id(game.current_color()).to_string();
+            game.make_move(&player_id, x, y).unwrap();
+        }
+        let mut board = *game.board();
+        board.set_hole(0, 7);
+        let color = game.current_color();
+
+        let (key, symmetry) = canonical_key(&board, color);
+        assert_eq!(key, position_key(&symmetry.transform(&board), color));
+        for symmetry in Symmetry::ALL {
+            let transformed = symmetry.transform(&board);
+            assert_eq!(canonical_key(&transformed, color).0, key);
+            assert_eq!(symmetry.inverse().transform(&transformed), board);
+        }
+    }
+}